@@ -0,0 +1,140 @@
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Orientation, Label, TreeView, TreeViewColumn, TreeStore, TreeIter,
+    CellRendererText, ScrolledWindow, Adjustment};
+use glib::Type;
+use super::super::logic::chessgame::ChessGame;
+use super::super::logic::variation_tree::VariationTree;
+
+const LABEL_COLUMN: u32 = 0;
+const NODE_COLUMN: u32 = 1;
+
+/// Engine evaluation and variation tree shown in the analysis mode.
+#[derive(Clone)]
+pub struct AnalysisPanel
+{
+    container: GtkBox,
+    evaluation_label: Label,
+    tree_view: TreeView,
+    tree_store: TreeStore,
+}
+
+impl AnalysisPanel
+{
+    pub fn new() -> AnalysisPanel
+    {
+        let evaluation_label = Label::new(None);
+        evaluation_label.set_line_wrap(true);
+
+        let tree_store = TreeStore::new(&[Type::String, Type::U32]);
+        let tree_view = TreeView::new();
+        tree_view.set_model(&tree_store);
+        tree_view.set_headers_visible(false);
+
+        let moves_column = TreeViewColumn::new();
+        let moves_cell = CellRendererText::new();
+        moves_column.pack_start(&moves_cell, true);
+        moves_column.add_attribute(&moves_cell, "text", LABEL_COLUMN as i32);
+        tree_view.append_column(&moves_column);
+
+        let scrolled_tree = ScrolledWindow::new(None::<&Adjustment>, None::<&Adjustment>);
+        scrolled_tree.set_size_request(180, -1);
+        scrolled_tree.add(&tree_view);
+
+        let container = GtkBox::new(
+            Orientation::Vertical,
+            5,
+        );
+        container.pack_start(
+            &evaluation_label,
+            false,
+            false,
+            5,
+        );
+        container.pack_start(
+            &scrolled_tree,
+            true,
+            true,
+            0,
+        );
+
+        AnalysisPanel {
+            container,
+            evaluation_label,
+            tree_view,
+            tree_store,
+        }
+    }
+
+    pub fn get_widget(&self) -> &GtkBox
+    {
+        &self.container
+    }
+
+    pub fn set_evaluation(&self, text: &str)
+    {
+        self.evaluation_label.set_text(text);
+    }
+
+    /// The callback is given the node of the variation tree activated by the user.
+    pub fn connect_node_activated<F: Fn(usize) + 'static>(&self, callback: F)
+    {
+        let tree_store = self.tree_store.clone();
+        self.tree_view.connect_row_activated(move |_tree_view, path, _column| {
+            let node = tree_store.get_iter(path)
+                .and_then(|iter| tree_store.get_value(&iter, NODE_COLUMN as i32).get::<u32>());
+            if let Some(node) = node {
+                callback(node as usize);
+            }
+        });
+    }
+
+    pub fn update_tree(&self, game: &ChessGame)
+    {
+        self.tree_store.clear();
+
+        let tree = game.get_variation_tree();
+        let mut current_row = None;
+        if let Some(first) = tree.get_children(None).first() {
+            self.add_line(tree, *first, None, game.get_current_node(), &mut current_row);
+        }
+        self.tree_view.expand_all();
+
+        if let Some(row) = current_row {
+            self.tree_view.get_selection().select_iter(&row);
+            if let Some(path) = self.tree_store.get_path(&row) {
+                self.tree_view.scroll_to_cell(&path, None, false, 0.0, 0.0);
+            }
+        }
+    }
+
+    /// Adds the line starting at the given node, the other choices
+    /// for a move being nested under the row of that move.
+    fn add_line(&self, tree: &VariationTree, first: usize, parent_row: Option<&TreeIter>,
+        current_node: Option<usize>, current_row: &mut Option<TreeIter>)
+    {
+        let mut node = first;
+        loop {
+            let row = self.tree_store.insert_with_values(
+                parent_row,
+                None,
+                &[LABEL_COLUMN, NODE_COLUMN],
+                &[&tree.get_move_label(node), &(node as u32)],
+            );
+            if current_node == Some(node) {
+                *current_row = Some(row.clone());
+            }
+
+            let parent = tree.get_node(node).parent;
+            if tree.get_children(parent).first() == Some(&node) {
+                tree.get_children(parent).iter().skip(1).for_each(|alternative| {
+                    self.add_line(tree, *alternative, Some(&row), current_node, current_row);
+                });
+            }
+
+            match tree.get_children(Some(node)).first() {
+                Some(next) => node = *next,
+                None => break
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,182 @@
+use pleco::core::{Piece};
+use pleco::core::sq::{SQ};
+
+/// Duration of a move animation, in microseconds as the GTK frame clock.
+pub const ANIMATION_DURATION_US: i64 = 200_000;
+
+#[derive(Clone, Debug)]
+pub struct SlidingPiece
+{
+    pub piece: Piece,
+    pub from: SQ,
+    pub to: SQ,
+}
+
+/// The pieces going from one position to another : the pieces sliding from their old cell
+/// to their new one, and the pieces which disappear, fading out on their old cell.
+#[derive(Clone, Debug)]
+pub struct Animation
+{
+    pub sliding: Vec<SlidingPiece>,
+    pub fading: Vec<(SQ, Piece)>,
+    pub start_time: Option<i64>,
+    pub progress: f64,
+}
+
+impl Animation
+{
+    /// Compares the pieces of both positions, given for each of the 64 cells,
+    /// so that it works for a single move (castling, en passant, promotion included)
+    /// as well as for a jump in the moves history.
+    /// Gives None if nothing moves.
+    pub fn between(before: &[Piece], after: &[Piece]) -> Option<Animation>
+    {
+        let mut sources: Vec<usize> = (0..64).filter(|cell| {
+            before[*cell] != Piece::None && before[*cell] != after[*cell]
+        }).collect();
+        let targets: Vec<usize> = (0..64).filter(|cell| {
+            after[*cell] != Piece::None && before[*cell] != after[*cell]
+        }).collect();
+
+        let mut sliding = vec![];
+        let mut unmatched_targets = vec![];
+        targets.iter().for_each(|target| {
+            match take_nearest(&mut sources, *target, |source| before[source] == after[*target]) {
+                Some(source) => sliding.push(SlidingPiece {
+                    piece: after[*target],
+                    from: SQ::from(source as u8),
+                    to: SQ::from(*target as u8),
+                }),
+                None => unmatched_targets.push(*target)
+            }
+        });
+        // Promotions, or promotions taken back : a piece of the same side changing its type.
+        unmatched_targets.iter().for_each(|target| {
+            let same_side = |source: usize| before[source].player() == after[*target].player();
+            if let Some(source) = take_nearest(&mut sources, *target, same_side) {
+                sliding.push(SlidingPiece {
+                    piece: after[*target],
+                    from: SQ::from(source as u8),
+                    to: SQ::from(*target as u8),
+                });
+            }
+        });
+
+        let fading: Vec<(SQ, Piece)> = sources.iter()
+            .map(|source| (SQ::from(*source as u8), before[*source]))
+            .collect();
+
+        if sliding.is_empty() && fading.is_empty() {
+            None
+        }
+        else {
+            Some(Animation {
+                sliding,
+                fading,
+                start_time: None,
+                progress: 0.0,
+            })
+        }
+    }
+
+    /// Whether the piece on the cell must not be drawn at its place, as it is still sliding to it.
+    pub fn hides(&self, cell: SQ) -> bool
+    {
+        self.sliding.iter().any(|sliding_piece| sliding_piece.to == cell)
+    }
+
+    /// Updates the progress for the given frame time, giving false once the animation is over.
+    pub fn update(&mut self, frame_time: i64) -> bool
+    {
+        let start_time = *self.start_time.get_or_insert(frame_time);
+        self.progress = ((frame_time - start_time) as f64 / ANIMATION_DURATION_US as f64).min(1.0);
+        self.progress < 1.0
+    }
+}
+
+fn take_nearest<F: Fn(usize) -> bool>(sources: &mut Vec<usize>, target: usize, accepts: F) -> Option<usize>
+{
+    let distance = |source: usize| {
+        let file_distance = (source % 8) as i32 - (target % 8) as i32;
+        let rank_distance = (source / 8) as i32 - (target / 8) as i32;
+        file_distance * file_distance + rank_distance * rank_distance
+    };
+    let (index, _source) = sources.iter().cloned().enumerate()
+        .filter(|(_index, source)| accepts(*source))
+        .min_by_key(|(_index, source)| distance(*source))?;
+    Some(sources.remove(index))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::super::super::logic::test_helpers::{cell, game};
+
+    /// The animation of the given move, played from the given position.
+    fn animation(fen: &str, move_text: &str) -> Animation
+    {
+        let before = game(fen, &[]).get_pieces();
+        let after = game(fen, &[move_text]).get_pieces();
+        Animation::between(&before, &after).expect("Nothing moves !")
+    }
+
+    fn sliding(animation: &Animation) -> Vec<(Piece, SQ, SQ)>
+    {
+        animation.sliding.iter()
+            .map(|sliding_piece| (sliding_piece.piece, sliding_piece.from, sliding_piece.to))
+            .collect()
+    }
+
+    #[test]
+    fn quiet_move()
+    {
+        let animation = animation("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e4");
+        assert_eq!(sliding(&animation), vec![(Piece::WhitePawn, cell("e2"), cell("e4"))]);
+        assert!(animation.fading.is_empty());
+        assert!(animation.hides(cell("e4")));
+        assert!(! animation.hides(cell("e2")));
+    }
+
+    #[test]
+    fn capture()
+    {
+        let animation = animation("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "exd5");
+        assert_eq!(sliding(&animation), vec![(Piece::WhitePawn, cell("e4"), cell("d5"))]);
+        assert_eq!(animation.fading, vec![(cell("d5"), Piece::BlackPawn)]);
+    }
+
+    #[test]
+    fn castling_slides_both_pieces()
+    {
+        let animation = animation("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "O-O");
+        assert_eq!(sliding(&animation), vec![
+            (Piece::WhiteRook, cell("h1"), cell("f1")),
+            (Piece::WhiteKing, cell("e1"), cell("g1")),
+        ]);
+        assert!(animation.fading.is_empty());
+    }
+
+    #[test]
+    fn en_passant()
+    {
+        let animation = animation("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "exd6");
+        assert_eq!(sliding(&animation), vec![(Piece::WhitePawn, cell("e5"), cell("d6"))]);
+        assert_eq!(animation.fading, vec![(cell("d5"), Piece::BlackPawn)]);
+    }
+
+    #[test]
+    fn promotion()
+    {
+        let animation = animation("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=Q");
+        assert_eq!(sliding(&animation), vec![(Piece::WhiteQueen, cell("a7"), cell("a8"))]);
+        assert!(animation.fading.is_empty());
+    }
+
+    #[test]
+    fn nothing_moves()
+    {
+        let pieces = game("4k3/8/8/8/8/8/8/4K3 w - - 0 1", &[]).get_pieces();
+        assert!(Animation::between(&pieces, &pieces).is_none());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,760 @@
+use std::collections::HashMap;
+use gdk::prelude::*;
+use gdk_pixbuf::Pixbuf;
+use cairo::{Context, RadialGradient, Pattern, Gradient};
+use cairo::enums::{FontSlant, FontWeight};
+use pleco::core::{Piece, PieceType, Player};
+use pleco::core::sq::{SQ};
+use super::animation::Animation;
+use super::promotion_picker::PromotionPicker;
+use super::geometry::BoardGeometry;
+use super::theme::Theme;
+use super::super::logic::annotation::Annotation;
+use super::super::logic::coordinates::{CoordinatesOptions, CoordinatesPlacement};
+
+/// A piece being dragged by the user, drawn under the pointer instead of on its cell.
+#[derive(Clone, Debug)]
+pub struct DraggedPiece
+{
+    pub piece: Piece,
+    pub start_cell: SQ,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// How the pieces of the position are drawn, for the blindfold training.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PiecesVisibility
+{
+    /// With the given opacity.
+    Shown(f64),
+    Hidden,
+    /// Discs of the piece colour, without the piece type.
+    Discs,
+    HiddenSide(Player),
+}
+
+/// Everything shown on the board, so that it can be painted on any cairo surface :
+/// the board widget as well as an image, SVG or PDF diagram.
+/// The board is painted from the top left corner of the context.
+pub struct BoardPainter<'a>
+{
+    pub geometry: BoardGeometry,
+    pub theme: &'a Theme,
+    pub pieces_images: &'a HashMap<char, Pixbuf>,
+    /// The piece of each of the 64 cells.
+    pub pieces: Vec<Piece>,
+    pub pieces_visibility: PiecesVisibility,
+    pub white_turn: bool,
+    pub last_move: Option<(SQ, SQ)>,
+    pub checked_king_cell: Option<SQ>,
+    pub premoves: Vec<(SQ, SQ)>,
+    pub selected_cell: Option<SQ>,
+    pub legal_destinations: Vec<(SQ, bool)>,
+    pub cursor_cell: Option<SQ>,
+    pub annotations: Vec<Annotation>,
+    pub dragged_piece: Option<DraggedPiece>,
+    pub animation: Option<Animation>,
+    pub promotion_picker: Option<PromotionPicker>,
+    pub coordinates: CoordinatesOptions,
+    pub show_player_turn: bool,
+}
+
+impl<'a> BoardPainter<'a>
+{
+    /// A painter showing only the given pieces, with coordinates and player turn.
+    pub fn new(geometry: BoardGeometry, theme: &'a Theme, pieces_images: &'a HashMap<char, Pixbuf>,
+        pieces: Vec<Piece>, white_turn: bool) -> BoardPainter<'a>
+    {
+        BoardPainter {
+            geometry,
+            theme,
+            pieces_images,
+            pieces,
+            pieces_visibility: PiecesVisibility::Shown(1.0),
+            white_turn,
+            last_move: None,
+            checked_king_cell: None,
+            premoves: vec![],
+            selected_cell: None,
+            legal_destinations: vec![],
+            cursor_cell: None,
+            annotations: vec![],
+            dragged_piece: None,
+            animation: None,
+            promotion_picker: None,
+            coordinates: CoordinatesOptions::default(),
+            show_player_turn: true,
+        }
+    }
+
+    pub fn paint(&self, cr: &Context)
+    {
+        self.draw_background(cr);
+        self.draw_cells(cr);
+        self.draw_last_move(cr);
+        self.draw_check(cr);
+        self.draw_premoves(cr);
+        self.draw_selected_cell(cr);
+        self.draw_legal_destinations(cr);
+        self.draw_cursor(cr);
+        self.draw_pieces(cr);
+        self.draw_animation(cr);
+        self.draw_annotations(cr);
+        self.draw_dragged_piece(cr);
+        self.draw_promotion_picker(cr);
+        self.draw_coordinates(cr);
+        if self.show_player_turn {
+            self.draw_player_turn(cr);
+        }
+    }
+
+    fn draw_background(&self, cr: &Context)
+    {
+        let background_color = self.theme.background;
+        cr.set_source_rgb(
+            background_color[0],
+            background_color[1],
+            background_color[2],
+        );
+        cr.paint();
+    }
+
+    fn draw_cells(&self, cr: &Context)
+    {
+        (0..8).for_each(|rank| {
+            (0..8).for_each(|file| {
+                let white_cell_color = self.theme.light_cells;
+                let black_cell_color = self.theme.dark_cells;
+
+                let is_white_cell = (file + rank) % 2 == 0;
+                let cell_color = if is_white_cell {white_cell_color} else {black_cell_color};
+
+                let rect_x = self.geometry.margins.left + self.geometry.cells_size * (file as f64);
+                let rect_y = self.geometry.margins.top + self.geometry.cells_size * (rank as f64);
+                let rect_size = self.geometry.cells_size;
+
+                cr.rectangle(
+                    rect_x,
+                    rect_y,
+                    rect_size,
+                    rect_size,
+                );
+                cr.set_source_rgb(
+                    cell_color[0],
+                    cell_color[1],
+                    cell_color[2],
+                );
+                cr.fill();
+            });
+        });
+    }
+
+    fn fill_cell(&self, cr: &Context, cell: SQ)
+    {
+        let (x, y) = self.geometry.cell_origin(cell);
+        cr.rectangle(x, y, self.geometry.cells_size, self.geometry.cells_size);
+        cr.fill();
+    }
+
+    fn draw_last_move(&self, cr: &Context)
+    {
+        if let Some((start_cell, end_cell)) = self.last_move {
+            let color = self.theme.last_move;
+            cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+            self.fill_cell(cr, start_cell);
+            self.fill_cell(cr, end_cell);
+        }
+    }
+
+    fn draw_premoves(&self, cr: &Context)
+    {
+        let color = self.theme.premoves;
+        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+        self.premoves.iter().for_each(|(start_cell, end_cell)| {
+            self.fill_cell(cr, *start_cell);
+            self.fill_cell(cr, *end_cell);
+        });
+    }
+
+    fn draw_selected_cell(&self, cr: &Context)
+    {
+        if let Some(cell) = self.selected_cell {
+            let color = self.theme.selected_cell;
+            cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+            self.fill_cell(cr, cell);
+        }
+    }
+
+    fn draw_cursor(&self, cr: &Context)
+    {
+        if let Some(cell) = self.cursor_cell {
+            let cells_size = self.geometry.cells_size;
+            let (x, y) = self.geometry.cell_origin(cell);
+            let color = self.theme.selected_cell;
+            cr.set_source_rgb(color[0], color[1], color[2]);
+            cr.set_line_width(cells_size * 0.06);
+            cr.rectangle(x + cells_size * 0.03, y + cells_size * 0.03, cells_size * 0.94, cells_size * 0.94);
+            cr.stroke();
+        }
+    }
+
+    /// A red glow under the king of the side to move when it is in check.
+    fn draw_check(&self, cr: &Context)
+    {
+        if let Some(king_cell) = self.checked_king_cell {
+            let cells_size = self.geometry.cells_size;
+            let (x, y) = self.geometry.cell_origin(king_cell);
+            let (center_x, center_y) = self.geometry.cell_center(king_cell);
+
+            let color = self.theme.check;
+            let glow = RadialGradient::new(center_x, center_y, 0.0, center_x, center_y, cells_size * 0.7);
+            glow.add_color_stop_rgba(0.0, color[0], color[1], color[2], 1.0);
+            glow.add_color_stop_rgba(0.5, color[0], color[1], color[2], 0.8);
+            glow.add_color_stop_rgba(1.0, color[0], color[1], color[2], 0.0);
+            cr.set_source(&Pattern::RadialGradient(glow));
+            cr.rectangle(x, y, cells_size, cells_size);
+            cr.fill();
+        }
+    }
+
+    /// Dots on the free cells where the dragged piece can go, and rings on the cells it can capture on.
+    fn draw_legal_destinations(&self, cr: &Context)
+    {
+        let cells_size = self.geometry.cells_size;
+        let color = self.theme.legal_destinations;
+        cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+        self.legal_destinations.iter().for_each(|(cell, capture)| {
+            let (center_x, center_y) = self.geometry.cell_center(*cell);
+
+            cr.new_sub_path();
+            if *capture {
+                cr.set_line_width(cells_size * 0.08);
+                cr.arc(center_x, center_y, cells_size * 0.45, 0.0, 2.0 * std::f64::consts::PI);
+                cr.stroke();
+            }
+            else {
+                cr.arc(center_x, center_y, cells_size * 0.15, 0.0, 2.0 * std::f64::consts::PI);
+                cr.fill();
+            }
+        });
+    }
+
+    /// Paints the image of the piece at the given point, with the given opacity.
+    fn draw_piece_image(&self, cr: &Context, piece: Piece, x: f64, y: f64, alpha: f64)
+    {
+        let piece_char = piece.character().expect("Failed to get piece char value !");
+        if let Some(image) = self.pieces_images.get(&piece_char) {
+            cr.set_source_pixbuf(
+                image,
+                x,
+                y
+            );
+            cr.paint_with_alpha(alpha);
+        }
+    }
+
+    /// Paints a piece of the position, whose image would be at the given point,
+    /// as the pieces visibility allows it.
+    fn draw_position_piece(&self, cr: &Context, piece: Piece, x: f64, y: f64, alpha: f64)
+    {
+        match self.pieces_visibility {
+            PiecesVisibility::Shown(opacity) => self.draw_piece_image(cr, piece, x, y, alpha * opacity),
+            PiecesVisibility::Hidden => {},
+            PiecesVisibility::HiddenSide(player) if piece.player() == Some(player) => {},
+            PiecesVisibility::HiddenSide(_) => self.draw_piece_image(cr, piece, x, y, alpha),
+            PiecesVisibility::Discs => {
+                let cells_size = self.geometry.cells_size;
+                let white = piece.player() == Some(Player::White);
+                let color = if white { [1.0, 1.0, 1.0] } else { [0.0, 0.0, 0.0] };
+                cr.new_sub_path();
+                cr.arc(x + cells_size * 0.4, y + cells_size * 0.4, cells_size * 0.32, 0.0, 2.0 * std::f64::consts::PI);
+                cr.set_source_rgba(color[0], color[1], color[2], alpha);
+                cr.fill_preserve();
+                cr.set_source_rgba(0.3, 0.3, 0.3, alpha);
+                cr.set_line_width(cells_size * 0.03);
+                cr.stroke();
+            },
+        }
+    }
+
+    fn draw_pieces(&self, cr: &Context)
+    {
+        let offset = self.geometry.cells_size * 0.1;
+        (0..64u8).for_each(|index| {
+            let cell = SQ::from(index);
+            let piece = self.pieces[index as usize];
+            if piece == Piece::None {
+                return;
+            }
+
+            let dragged_piece = match self.dragged_piece {
+                Some(ref dragged_piece) => dragged_piece.start_cell == cell,
+                None => false
+            };
+            let animated_piece = match self.animation {
+                Some(ref animation) => animation.hides(cell),
+                None => false
+            };
+
+            if ! dragged_piece && ! animated_piece {
+                let (x, y) = self.geometry.cell_origin(cell);
+                self.draw_position_piece(cr, piece, x + offset, y + offset, 1.0);
+            }
+        });
+    }
+
+    /// The arrows and circles of the position.
+    fn draw_annotations(&self, cr: &Context)
+    {
+        let cells_size = self.geometry.cells_size;
+        let cell_center = |cell: SQ| self.geometry.cell_center(cell);
+        self.annotations.iter().for_each(|annotation| {
+            let color = self.theme.annotation_color(annotation.get_color());
+            cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+
+            match annotation {
+                Annotation::Circle(cell, _) => {
+                    let (center_x, center_y) = cell_center(*cell);
+                    cr.new_sub_path();
+                    cr.set_line_width(cells_size * 0.08);
+                    cr.arc(center_x, center_y, cells_size * 0.45, 0.0, 2.0 * std::f64::consts::PI);
+                    cr.stroke();
+                },
+                Annotation::Arrow(start_cell, end_cell, _) => {
+                    let (start_x, start_y) = cell_center(*start_cell);
+                    let (end_x, end_y) = cell_center(*end_cell);
+                    let angle = (end_y - start_y).atan2(end_x - start_x);
+                    let head_length = cells_size * 0.45;
+                    let head_base_x = end_x - head_length * angle.cos();
+                    let head_base_y = end_y - head_length * angle.sin();
+
+                    cr.set_line_width(cells_size * 0.18);
+                    cr.move_to(start_x, start_y);
+                    cr.line_to(head_base_x, head_base_y);
+                    cr.stroke();
+
+                    let head_half_width = cells_size * 0.25;
+                    cr.move_to(end_x, end_y);
+                    cr.line_to(head_base_x - head_half_width * angle.sin(), head_base_y + head_half_width * angle.cos());
+                    cr.line_to(head_base_x + head_half_width * angle.sin(), head_base_y - head_half_width * angle.cos());
+                    cr.close_path();
+                    cr.fill();
+                },
+            }
+        });
+    }
+
+    /// The sliding pieces between their cells, and the disappearing ones fading out.
+    fn draw_animation(&self, cr: &Context)
+    {
+        let animation = match self.animation {
+            Some(ref animation) => animation,
+            None => return
+        };
+        let offset = self.geometry.cells_size * 0.1;
+
+        animation.fading.iter().for_each(|(cell, piece)| {
+            let (x, y) = self.geometry.cell_origin(*cell);
+            self.draw_position_piece(cr, *piece, x + offset, y + offset, 1.0 - animation.progress);
+        });
+
+        animation.sliding.iter().for_each(|sliding_piece| {
+            let (from_x, from_y) = self.geometry.cell_origin(sliding_piece.from);
+            let (to_x, to_y) = self.geometry.cell_origin(sliding_piece.to);
+            self.draw_position_piece(
+                cr,
+                sliding_piece.piece,
+                from_x + (to_x - from_x) * animation.progress + offset,
+                from_y + (to_y - from_y) * animation.progress + offset,
+                1.0
+            );
+        });
+    }
+
+    fn draw_dragged_piece(&self, cr: &Context)
+    {
+        if let Some(ref dragged_piece) = self.dragged_piece {
+            let piece_pointer_x = dragged_piece.x - self.geometry.cells_size * 0.4;
+            let piece_pointer_y = dragged_piece.y - self.geometry.cells_size * 0.4;
+            self.draw_position_piece(cr, dragged_piece.piece, piece_pointer_x, piece_pointer_y, 1.0);
+        }
+    }
+
+    /// The board darkened, with the promotion choices over the promotion cell column.
+    fn draw_promotion_picker(&self, cr: &Context)
+    {
+        let picker = match self.promotion_picker {
+            Some(picker) => picker,
+            None => return
+        };
+        let cells_size = self.geometry.cells_size;
+        let margins = self.geometry.margins;
+        cr.set_source_rgba(0.0, 0.0, 0.0, 0.5);
+        cr.rectangle(margins.left, margins.top, cells_size * 8.0, cells_size * 8.0);
+        cr.fill();
+
+        picker.choice_cells().iter().for_each(|(cell, piece_type)| {
+            let (x, y) = self.geometry.cell_origin(*cell);
+            let (center_x, center_y) = self.geometry.cell_center(*cell);
+            cr.new_sub_path();
+            cr.arc(center_x, center_y, cells_size * 0.48, 0.0, 2.0 * std::f64::consts::PI);
+            cr.set_source_rgb(0.9, 0.9, 0.9);
+            cr.fill();
+
+            let piece_char = promotion_char(*piece_type, picker.is_white());
+            if let Some(image) = self.pieces_images.get(&piece_char) {
+                cr.set_source_pixbuf(
+                    image,
+                    x + cells_size * 0.1,
+                    y + cells_size * 0.1
+                );
+                cr.paint();
+            }
+        });
+    }
+
+    /// The files letters and the ranks digits, outside of the board or inside its border cells.
+    fn draw_coordinates(&self, cr: &Context)
+    {
+        let cells_size = self.geometry.cells_size;
+        let margins = self.geometry.margins;
+        let placement = self.coordinates.placement;
+        let first_letter = if self.coordinates.uppercase { b'A' } else { b'a' };
+        let file_letter = |file: u8| ((first_letter + file) as char).to_string();
+        let rank_digit = |rank: u8| ((b'1' + rank) as char).to_string();
+
+        cr.select_font_face(
+            "Sans Serif",
+            FontSlant::Normal,
+            FontWeight::Bold
+        );
+
+        match placement {
+            CoordinatesPlacement::Off => {},
+            CoordinatesPlacement::OutsideBottomLeft | CoordinatesPlacement::OutsideAllSides => {
+                let all_sides = placement == CoordinatesPlacement::OutsideAllSides;
+                let color = if self.coordinates.cells_colours { self.theme.light_cells } else { self.theme.coordinates };
+                cr.set_source_rgb(color[0], color[1], color[2]);
+                cr.set_font_size(cells_size * 0.38);
+
+                (0..8u8).for_each(|file| {
+                    let (cell_x, _cell_y) = self.geometry.cell_origin(SQ::from(file));
+                    let letter_x = cell_x + cells_size * 0.4;
+                    if all_sides {
+                        cr.move_to(letter_x, margins.top - cells_size * 0.1);
+                        cr.show_text(&file_letter(file));
+                    }
+                    cr.move_to(letter_x, margins.top + cells_size * 8.4);
+                    cr.show_text(&file_letter(file));
+                });
+
+                (0..8u8).for_each(|rank| {
+                    let (_cell_x, cell_y) = self.geometry.cell_origin(SQ::from(8 * rank));
+                    let digit_y = cell_y + cells_size * 0.7;
+                    cr.move_to(margins.left - cells_size * 0.4, digit_y);
+                    cr.show_text(&rank_digit(rank));
+                    if all_sides {
+                        cr.move_to(margins.left + cells_size * 8.1, digit_y);
+                        cr.show_text(&rank_digit(rank));
+                    }
+                });
+            },
+            CoordinatesPlacement::InsideCorners => {
+                let (bottom_rank, left_file) = if self.geometry.reversed { (7, 7) } else { (0, 0) };
+                cr.set_font_size(cells_size * 0.25);
+
+                (0..8u8).for_each(|file| {
+                    let cell = SQ::from(file + 8 * bottom_rank);
+                    let (cell_x, cell_y) = self.geometry.cell_origin(cell);
+                    let letter = file_letter(file);
+                    let letter_width = cr.text_extents(&letter).x_advance;
+                    self.set_inside_label_color(cr, cell);
+                    cr.move_to(cell_x + cells_size * 0.95 - letter_width, cell_y + cells_size * 0.95);
+                    cr.show_text(&letter);
+                });
+
+                (0..8u8).for_each(|rank| {
+                    let cell = SQ::from(left_file + 8 * rank);
+                    let (cell_x, cell_y) = self.geometry.cell_origin(cell);
+                    self.set_inside_label_color(cr, cell);
+                    cr.move_to(cell_x + cells_size * 0.05, cell_y + cells_size * 0.28);
+                    cr.show_text(&rank_digit(rank));
+                });
+            },
+        }
+    }
+
+    /// The coordinates colour, or the colour of the other cells so that the label stands out.
+    fn set_inside_label_color(&self, cr: &Context, cell: SQ)
+    {
+        let light_cell = (cell.file_idx_of_sq() + cell.rank_idx_of_sq()) % 2 == 1;
+        let color = if ! self.coordinates.cells_colours { self.theme.coordinates }
+            else if light_cell { self.theme.dark_cells }
+            else { self.theme.light_cells };
+        cr.set_source_rgb(color[0], color[1], color[2]);
+    }
+
+    /// A disc of the colour of the side to move, in the bottom right corner of the margins,
+    /// or at the bottom of the right margin.
+    fn draw_player_turn(&self, cr: &Context)
+    {
+        let color = if self.white_turn { [1.0, 1.0, 1.0] } else { [0.0, 0.0, 0.0] };
+        let cells_size = self.geometry.cells_size;
+        let margins = self.geometry.margins;
+        let center_x = margins.left + cells_size * 8.0 + margins.right * 0.5;
+        let center_y = if margins.bottom > 0.0 {
+            margins.top + cells_size * 8.0 + margins.bottom * 0.5
+        } else {
+            margins.top + cells_size * 8.0 - margins.right * 0.5
+        };
+        let radius = cells_size * 0.25;
+        cr.new_sub_path();
+        cr.arc(center_x, center_y, radius, 0.0, 2.0 * std::f64::consts::PI);
+        cr.set_source_rgb(
+            color[0],
+            color[1],
+            color[2],
+        );
+        cr.fill();
+    }
+}
+
+fn promotion_char(piece_type: PieceType, white: bool) -> char
+{
+    let piece_char = match piece_type {
+        PieceType::R => 'r',
+        PieceType::B => 'b',
+        PieceType::N => 'n',
+        _ => 'q',
+    };
+    if white { piece_char.to_ascii_uppercase() } else { piece_char }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::env;
+    use std::fs::File;
+    use std::path::PathBuf;
+    use cairo::{Format, ImageSurface};
+    use super::*;
+    use super::super::piece_set::PieceSet;
+    use super::super::geometry::Margins;
+    use super::super::theme::{theme_by_name, DEFAULT_THEME};
+    use super::super::super::logic::annotation::AnnotationColor;
+    use super::super::super::logic::chessgame::ChessGame;
+    use super::super::super::logic::test_helpers::{cell, game};
+
+    const CELLS_SIZE: f64 = 40.0;
+    /// Channel difference under which two pixels are considered the same,
+    /// as the fonts and the images scaling slightly differ from one system to another.
+    const CHANNEL_TOLERANCE: i32 = 32;
+    /// Part of the pixels allowed to differ beyond the channel tolerance, in the cells
+    /// and in the margins, checked apart so that a missing coordinate is not lost among the cells.
+    const DIFFERENT_PIXELS_TOLERANCE: f64 = 0.001;
+
+    /// The theme and the pieces images used by all the tests.
+    struct Fixture
+    {
+        theme: Theme,
+        pieces_images: HashMap<char, Pixbuf>,
+    }
+
+    impl Fixture
+    {
+        fn new() -> Fixture
+        {
+            Fixture {
+                theme: theme_by_name(DEFAULT_THEME),
+                pieces_images: PieceSet::default_set().load_images((CELLS_SIZE * 0.8) as u32),
+            }
+        }
+
+        fn painter(&self, geometry: BoardGeometry, game: &ChessGame) -> BoardPainter<'_>
+        {
+            BoardPainter::new(geometry, &self.theme, &self.pieces_images, game.get_pieces(), game.is_white_turn())
+        }
+    }
+
+    fn geometry(reversed: bool) -> BoardGeometry
+    {
+        BoardGeometry::with_margins(CELLS_SIZE, Margins::uniform(CELLS_SIZE * 0.5), reversed)
+    }
+
+    /// Paints into an image compared to the reference image tests/golden/<name>.png.
+    /// With the UPDATE_GOLDEN_IMAGES environment variable set, the reference image is written instead.
+    fn check_rendering(name: &str, painter: &BoardPainter)
+    {
+        let (width, height) = (painter.geometry.board_width() as i32, painter.geometry.board_height() as i32);
+        let mut image = ImageSurface::create(Format::ARgb32, width, height).expect("Failed to create the image !");
+        painter.paint(&Context::new(&image));
+
+        let reference_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("golden")
+            .join(format!("{}.png", name));
+        if env::var_os("UPDATE_GOLDEN_IMAGES").is_some() {
+            let mut file = File::create(&reference_path).expect("Failed to create the reference image !");
+            image.write_to_png(&mut file).expect("Failed to write the reference image !");
+            return;
+        }
+
+        let mut file = File::open(&reference_path)
+            .unwrap_or_else(|e| panic!("Missing reference image {} ({})", reference_path.display(), e));
+        let mut reference = ImageSurface::create_from_png(&mut file).expect("Failed to read the reference image !");
+        assert_eq!((reference.get_width(), reference.get_height()), (width, height), "{} has changed size", name);
+
+        let (stride, reference_stride) = (image.get_stride() as usize, reference.get_stride() as usize);
+        let margins = painter.geometry.margins;
+        let board_size = painter.geometry.cells_size * 8.0;
+        let in_cells = |x: usize, y: usize| {
+            let (x, y) = (x as f64, y as f64);
+            x >= margins.left && x < margins.left + board_size && y >= margins.top && y < margins.top + board_size
+        };
+        // The pixels count and the different pixels count, in the cells and in the margins.
+        let mut cells_pixels = (0, 0);
+        let mut margins_pixels = (0, 0);
+        {
+            let data = image.get_data().expect("Failed to read the image !");
+            let reference_data = reference.get_data().expect("Failed to read the reference image !");
+            (0..height as usize).flat_map(|y| (0..width as usize).map(move |x| (x, y)))
+                .for_each(|(x, y)| {
+                    let pixel = &data[y * stride + x * 4..y * stride + x * 4 + 4];
+                    let reference_pixel = &reference_data[y * reference_stride + x * 4..y * reference_stride + x * 4 + 4];
+                    let different = pixel.iter().zip(reference_pixel.iter())
+                        .any(|(channel, reference_channel)| (*channel as i32 - *reference_channel as i32).abs() > CHANNEL_TOLERANCE);
+                    let region = if in_cells(x, y) { &mut cells_pixels } else { &mut margins_pixels };
+                    region.0 += 1;
+                    if different {
+                        region.1 += 1;
+                    }
+                });
+        }
+
+        [("cells", cells_pixels), ("margins", margins_pixels)].iter()
+            .filter(|(_region, (pixels, _different_pixels))| *pixels > 0)
+            .for_each(|(region, (pixels, different_pixels))| {
+                let different_part = *different_pixels as f64 / *pixels as f64;
+                if different_part > DIFFERENT_PIXELS_TOLERANCE {
+                    let actual_path = env::temp_dir().join(format!("{}.png", name));
+                    if let Ok(mut file) = File::create(&actual_path) {
+                        let _ = image.write_to_png(&mut file);
+                    }
+                    panic!("The {} of {} differ from their reference on {:.2} % of the pixels : see {}",
+                        region, name, different_part * 100.0, actual_path.display());
+                }
+            });
+    }
+
+    #[test]
+    fn start_position()
+    {
+        let fixture = Fixture::new();
+        let game = game("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[]);
+        let painter = fixture.painter(geometry(false), &game);
+        check_rendering("start_position", &painter);
+    }
+
+    #[test]
+    fn reversed_position()
+    {
+        let fixture = Fixture::new();
+        let game = game("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4", &["O-O"]);
+        let painter = fixture.painter(geometry(true), &game);
+        check_rendering("reversed_position", &painter);
+    }
+
+    #[test]
+    fn piece_dragged()
+    {
+        let fixture = Fixture::new();
+        let game = game("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[]);
+        let mut painter = fixture.painter(geometry(false), &game);
+        painter.selected_cell = Some(cell("g1"));
+        painter.legal_destinations = game.legal_destinations(cell("g1"));
+        painter.dragged_piece = Some(DraggedPiece {
+            piece: game.piece_at_cell(cell("g1")),
+            start_cell: cell("g1"),
+            x: 255.0,
+            y: 250.0,
+        });
+        check_rendering("piece_dragged", &painter);
+    }
+
+    #[test]
+    fn pieces_as_discs()
+    {
+        let fixture = Fixture::new();
+        let game = game("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4", &[]);
+        let mut painter = fixture.painter(geometry(false), &game);
+        painter.pieces_visibility = PiecesVisibility::Discs;
+        check_rendering("pieces_as_discs", &painter);
+    }
+
+    #[test]
+    fn pieces_hidden()
+    {
+        let fixture = Fixture::new();
+        let game = game("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4", &[]);
+        let mut painter = fixture.painter(geometry(false), &game);
+        painter.pieces_visibility = PiecesVisibility::Hidden;
+        painter.last_move = Some((cell("g1"), cell("f3")));
+        check_rendering("pieces_hidden", &painter);
+    }
+
+    #[test]
+    fn opponent_pieces_hidden()
+    {
+        let fixture = Fixture::new();
+        let game = game("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4", &[]);
+        let mut painter = fixture.painter(geometry(true), &game);
+        painter.pieces_visibility = PiecesVisibility::HiddenSide(Player::White);
+        check_rendering("opponent_pieces_hidden", &painter);
+    }
+
+    #[test]
+    fn highlights()
+    {
+        let fixture = Fixture::new();
+        let game = game("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7"]);
+        let mut painter = fixture.painter(geometry(false), &game);
+        painter.last_move = game.get_last_move().map(|last_move| (last_move.start_cell, last_move.end_cell));
+        painter.checked_king_cell = game.checked_king_cell();
+        painter.annotations = vec![
+            Annotation::Arrow(cell("c4"), cell("f7"), AnnotationColor::Green),
+            Annotation::Circle(cell("e8"), AnnotationColor::Red),
+        ];
+        check_rendering("highlights", &painter);
+    }
+
+    #[test]
+    fn coordinates_inside_corners()
+    {
+        let fixture = Fixture::new();
+        let game = game("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[]);
+        let margins = Margins::for_layout(CoordinatesPlacement::InsideCorners, false);
+        let geometry = BoardGeometry::with_margins(CELLS_SIZE, margins.scaled(CELLS_SIZE), true);
+        let mut painter = fixture.painter(geometry, &game);
+        painter.coordinates = CoordinatesOptions {
+            placement: CoordinatesPlacement::InsideCorners,
+            uppercase: false,
+            cells_colours: true,
+        };
+        painter.show_player_turn = false;
+        check_rendering("coordinates_inside_corners", &painter);
+    }
+
+    #[test]
+    fn coordinates_bottom_left()
+    {
+        let fixture = Fixture::new();
+        let game = game("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[]);
+        let margins = Margins::for_layout(CoordinatesPlacement::OutsideBottomLeft, true);
+        let geometry = BoardGeometry::with_margins(CELLS_SIZE, margins.scaled(CELLS_SIZE), false);
+        let mut painter = fixture.painter(geometry, &game);
+        painter.coordinates.placement = CoordinatesPlacement::OutsideBottomLeft;
+        check_rendering("coordinates_bottom_left", &painter);
+    }
+}
\ No newline at end of file
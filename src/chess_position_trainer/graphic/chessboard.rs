@@ -1,17 +1,43 @@
 use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_void};
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell, Ref};
+use std::time::{Duration, Instant};
 use gtk::prelude::*;
 use gdk::prelude::*;
-use gdk::{EventMask, EventType};
-use gtk::{DrawingArea, Dialog, Orientation, Box as GBox, Button, Image};
+use gdk::{EventMask, EventType, ModifierType};
+use gdk::enums::key::{self, Key};
+use gtk::{DrawingArea, Widget};
+use glib::translate::ToGlibPtr;
 use gdk_pixbuf::Pixbuf;
-use cairo::Context;
-use cairo::enums::{FontSlant, FontWeight};
-use pleco::core::{Piece, PieceType};
+use cairo::{Context};
+use pleco::core::{Piece, PieceType, Player};
 use pleco::core::sq::{SQ};
-use super::load_image;
-use super::super::logic::chessgame::ChessGame;
+use super::piece_set::PieceSet;
+use super::animation::Animation;
+use super::promotion_picker::PromotionPicker;
+use super::geometry::{BoardGeometry, Margins};
+use super::board_painter::{BoardPainter, DraggedPiece, PiecesVisibility};
+use super::theme::{Theme, DEFAULT_THEME, theme_by_name};
+use super::super::logic::chessgame::{ChessGame, can_reach};
+use super::super::logic::move_outcome::{MoveOutcome, MoveError};
+use super::super::logic::game_result::{GameResult};
+use super::super::logic::variation_tree::{Navigation};
+use super::super::logic::annotation::{Annotation, AnnotationColor};
+use super::super::logic::blindfold::{Blindfold, FADE_DURATION, fading_opacity};
+use super::super::logic::coordinates::CoordinatesOptions;
+
+// The GTK bindings do not wrap the ATK objects, which are part of the GTK libraries all the same.
+extern "C" {
+    fn atk_object_set_description(accessible: *mut c_void, description: *const c_char);
+}
+
+/// How many pieces sizes keep their images, so that resizing does not load them again and again.
+const PIECES_IMAGES_CACHE_SIZES: usize = 4;
+
+type MoveDoneHandler = Rc<dyn Fn(&MoveOutcome)>;
+type MessageHandler = Rc<dyn Fn(&str)>;
 
 #[derive(Clone)]
 pub struct ChessBoard
@@ -19,9 +45,34 @@ pub struct ChessBoard
     drawing_area: DrawingArea,
     reversed: bool,
     logic: RefCell<ChessGame>,
-    cells_size: u32,
+    cells_size: Cell<u32>,
+    allocated_size: Cell<(i32, i32)>,
+    coordinates: Cell<CoordinatesOptions>,
+    board_origin: Cell<(f64, f64)>,
     moved_piece: RefCell<Option<MovedPiece>>,
-    pieces_images: HashMap<char, Pixbuf>,
+    legal_destinations: RefCell<Vec<(SQ, bool)>>,
+    selected_cell: Cell<Option<SQ>>,
+    premoves: RefCell<Vec<(SQ, SQ)>>,
+    promotion_picker: Cell<Option<PromotionPicker>>,
+    auto_queen: Cell<bool>,
+    premove_discarded_handlers: RefCell<Vec<MessageHandler>>,
+    cursor_cell: Cell<SQ>,
+    cursor_visible: Cell<bool>,
+    drawn_annotation: Cell<Option<(SQ, SQ, AnnotationColor)>>,
+    theme: RefCell<Theme>,
+    piece_set: RefCell<PieceSet>,
+    animation: Rc<RefCell<Option<Animation>>>,
+    animation_ticking: Rc<Cell<bool>>,
+    blindfold: Cell<Blindfold>,
+    fade_delay: Rc<Cell<Duration>>,
+    shown_since: Rc<Cell<Instant>>,
+    fade_ticking: Rc<Cell<bool>>,
+    pieces_images: RefCell<HashMap<char, Pixbuf>>,
+    /// The images for the last pieces sizes, the most recently used last.
+    pieces_images_cache: RefCell<Vec<(u32, HashMap<char, Pixbuf>)>>,
+    move_done_handlers: RefCell<Vec<MoveDoneHandler>>,
+    position_changed_handlers: RefCell<Vec<Rc<dyn Fn()>>>,
+    human_side: Cell<Option<Player>>,
 }
 
 #[derive(Clone, Debug)]
@@ -31,15 +82,8 @@ struct MovedPiece
     coords_x: f64,
     coords_y: f64,
     start_file: u8,
-    start_rank: u8
-}
-
-enum PromotionType
-{
-    QUEEN = 1,
-    ROOK = 2,
-    BISHOP = 4,
-    KNIGHT = 8
+    start_rank: u8,
+    already_selected: bool,
 }
 
 impl MovedPiece {
@@ -72,66 +116,213 @@ impl ChessBoard
         self.drawing_area.queue_draw();
     }
 
+    pub fn set_reversed(&mut self, reversed: bool)
+    {
+        self.reversed = reversed;
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn is_reversed(&self) -> bool
+    {
+        self.reversed
+    }
+
+    pub fn set_theme(&self, theme: Theme)
+    {
+        self.theme.replace(theme);
+        self.drawing_area.queue_draw();
+    }
+
+    /// Lays the board out again, so that it takes the space of the margins not needed any more.
+    pub fn set_coordinates(&self, options: CoordinatesOptions)
+    {
+        self.coordinates.set(options);
+        let (width, height) = self.allocated_size.get();
+        self.handle_resize(width, height);
+    }
+
+    /// Hides the pieces according to the blindfold mode, the fading mode
+    /// waiting for the given delay after each position change.
+    pub fn set_blindfold(&self, blindfold: Blindfold, fade_delay_seconds: u32)
+    {
+        self.blindfold.set(blindfold);
+        self.fade_delay.set(Duration::from_secs(fade_delay_seconds as u64));
+        self.restart_fading();
+        self.drawing_area.queue_draw();
+    }
+
+    /// Shows the pieces again, and redraws the board until they have faded out in the fading mode.
+    fn restart_fading(&self)
+    {
+        self.shown_since.set(Instant::now());
+        if self.blindfold.get() != Blindfold::Fade || self.fade_ticking.get() {
+            return;
+        }
+
+        self.fade_ticking.set(true);
+        gtk::timeout_add(50, {
+            let drawing_area = self.drawing_area.clone();
+            let shown_since = self.shown_since.clone();
+            let fade_ticking = self.fade_ticking.clone();
+            let fade_delay = self.fade_delay.clone();
+            move || {
+                drawing_area.queue_draw();
+                // The delay is read at each tick, as it may be changed while the pieces fade.
+                let fading = shown_since.get().elapsed() <= fade_delay.get() + FADE_DURATION;
+                fade_ticking.set(fading);
+                Continue(fading)
+            }
+        });
+    }
+
+    fn pieces_visibility(&self) -> PiecesVisibility
+    {
+        match self.blindfold.get() {
+            Blindfold::Off => PiecesVisibility::Shown(1.0),
+            Blindfold::HideAll => PiecesVisibility::Hidden,
+            Blindfold::Discs => PiecesVisibility::Discs,
+            Blindfold::HideOpponent => match self.human_side.get() {
+                Some(side) => PiecesVisibility::HiddenSide(side.other_player()),
+                None => PiecesVisibility::Shown(1.0)
+            },
+            Blindfold::Fade => PiecesVisibility::Shown(
+                fading_opacity(self.fade_delay.get(), self.shown_since.get().elapsed())
+            ),
+        }
+    }
+
+    /// Uses the given piece set, the images being loaded again at the current size.
+    pub fn set_piece_set(&self, piece_set: PieceSet)
+    {
+        if *self.piece_set.borrow() == piece_set {
+            return;
+        }
+        self.piece_set.replace(piece_set);
+        self.pieces_images_cache.borrow_mut().clear();
+
+        let pieces_size = (self.cells_size.get() as f64 * 0.8) as u32;
+        self.pieces_images.replace(self.cached_pieces_images(pieces_size));
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn load_position(&self, fen: &str) -> Result<(), String>
+    {
+        match ChessGame::new_from_fen(fen) {
+            Some(game_logic) => {
+                self.logic.replace(game_logic);
+                self.animation.replace(None);
+                self.premoves.borrow_mut().clear();
+                self.promotion_picker.set(None);
+                self.moved_piece.replace(None);
+                self.clear_selection();
+                self.notify_position_changed();
+                Ok(())
+            },
+            None => Err(format!("Bad FEN {} !", fen))
+        }
+    }
+
+    /// Restricts the mouse and typed moves to the given side,
+    /// or lets the user play both sides with None.
+    pub fn set_human_side(&self, side: Option<Player>)
+    {
+        self.human_side.set(side);
+    }
+
+    pub fn get_human_side(&self) -> Option<Player>
+    {
+        self.human_side.get()
+    }
+
+    fn is_human_turn(&self) -> bool
+    {
+        match self.human_side.get() {
+            Some(side) => (side == Player::White) == self.logic.borrow().is_white_turn(),
+            None => true
+        }
+    }
+
     pub fn get_drawing_area(&self) -> &DrawingArea
     {
         &self.drawing_area
     }
-    
-    fn load_pieces_images(size: u32) -> HashMap<char, Pixbuf> {
-        let mut images = HashMap::new();
-
-        let params: Vec<(char, &'static [u8], &str)> = vec![
-            ('P', include_bytes!("../../resources/Chess_pl.png"), "Failed to get white pawn image !"),
-            ('N', include_bytes!("../../resources/Chess_nl.png"), "Failed to get white knight image !"),
-            ('B', include_bytes!("../../resources/Chess_bl.png"), "Failed to get white bishop image !"),
-            ('R', include_bytes!("../../resources/Chess_rl.png"), "Failed to get white rook image !"),
-            ('Q', include_bytes!("../../resources/Chess_ql.png"), "Failed to get white queen image !"),
-            ('K', include_bytes!("../../resources/Chess_kl.png"), "Failed to get white king image !"),
-
-            ('p', include_bytes!("../../resources/Chess_pd.png"), "Failed to get black pawn image !"),
-            ('n', include_bytes!("../../resources/Chess_nd.png"), "Failed to get black knight image !"),
-            ('b', include_bytes!("../../resources/Chess_bd.png"), "Failed to get black bishop image !"),
-            ('r', include_bytes!("../../resources/Chess_rd.png"), "Failed to get black rook image !"),
-            ('q', include_bytes!("../../resources/Chess_qd.png"), "Failed to get black queen image !"),
-            ('k', include_bytes!("../../resources/Chess_kd.png"), "Failed to get black king image !"),
-        ];
-
-        params.iter().for_each(|(piece_code, image_binary, error_string)| {
-            images.insert(
-                *piece_code,
-                load_image(
-                    image_binary,
-                    size as i32
-                ).expect(error_string)
-            );
 
-        });
+    pub fn get_game(&self) -> Ref<'_, ChessGame>
+    {
+        self.logic.borrow()
+    }
+
+    pub fn resign(&self, white_resigns: bool)
+    {
+        self.logic.borrow_mut().resign(white_resigns);
+        self.drawing_area.queue_draw();
+    }
 
-        images
+    pub fn agree_draw(&self)
+    {
+        self.logic.borrow_mut().agree_draw();
+        self.drawing_area.queue_draw();
     }
 
+    pub fn claim_draw(&self) -> Result<GameResult, String>
+    {
+        let claim = self.logic.borrow_mut().claim_draw();
+        self.drawing_area.queue_draw();
+        claim
+    }
+    
     fn get_chessboard(initial_position: &str) -> Result<Rc<RefCell<ChessBoard>>, String>
     {
         let drawing_area = DrawingArea::new();
         drawing_area.add_events((
             EventMask::BUTTON1_MOTION_MASK.bits() |
+            EventMask::BUTTON3_MOTION_MASK.bits() |
             EventMask::BUTTON_PRESS_MASK.bits() |
-            EventMask::BUTTON_RELEASE_MASK.bits()
+            EventMask::BUTTON_RELEASE_MASK.bits() |
+            EventMask::KEY_PRESS_MASK.bits()
         ) as i32);
+        drawing_area.set_can_focus(true);
+        drawing_area.set_size_request(180, 180);
 
         let logic = ChessGame::new_from_fen(initial_position);
 
         match logic {
             Some(game_logic) => {
-                let pieces_images = ChessBoard::load_pieces_images((50f64 * 0.8) as u32);
+                let pieces_size = (50f64 * 0.8) as u32;
+                let pieces_images = PieceSet::default_set().load_images(pieces_size);
+                let pieces_images_cache = vec![(pieces_size, pieces_images.clone())];
 
                 let chess_board = ChessBoard {
                     drawing_area,
                     reversed: false,
                     logic: RefCell::new(game_logic),
-                    cells_size: 50u32,
+                    cells_size: Cell::new(50u32),
+                    allocated_size: Cell::new((450, 450)),
+                    coordinates: Cell::new(CoordinatesOptions::default()),
+                    board_origin: Cell::new((0.0, 0.0)),
                     moved_piece: RefCell::new(None),
-                    pieces_images,
+                    legal_destinations: RefCell::new(vec![]),
+                    selected_cell: Cell::new(None),
+                    premoves: RefCell::new(vec![]),
+                    promotion_picker: Cell::new(None),
+                    auto_queen: Cell::new(false),
+                    premove_discarded_handlers: RefCell::new(vec![]),
+                    cursor_cell: Cell::new(SQ::from(12)),
+                    cursor_visible: Cell::new(false),
+                    drawn_annotation: Cell::new(None),
+                    theme: RefCell::new(theme_by_name(DEFAULT_THEME)),
+                    piece_set: RefCell::new(PieceSet::default_set()),
+                    animation: Rc::new(RefCell::new(None)),
+                    animation_ticking: Rc::new(Cell::new(false)),
+                    blindfold: Cell::new(Blindfold::Off),
+                    fade_delay: Rc::new(Cell::new(Duration::from_secs(5))),
+                    shown_since: Rc::new(Cell::new(Instant::now())),
+                    fade_ticking: Rc::new(Cell::new(false)),
+                    pieces_images: RefCell::new(pieces_images),
+                    pieces_images_cache: RefCell::new(pieces_images_cache),
+                    move_done_handlers: RefCell::new(vec![]),
+                    position_changed_handlers: RefCell::new(vec![]),
+                    human_side: Cell::new(None),
                 };
 
                 let chess_board_ref = Rc::new(RefCell::new(chess_board));
@@ -144,12 +335,38 @@ impl ChessBoard
                     }
                 });
 
+                chess_board_ref.borrow().drawing_area.connect_size_allocate({
+                    let chess_board_ref = chess_board_ref.clone();
+                    move |_drawing_area, allocation| {
+                        chess_board_ref.borrow().handle_resize(allocation.width, allocation.height);
+                    }
+                });
+
+                chess_board_ref.borrow().drawing_area.connect_key_press_event({
+                    let chess_board_ref = chess_board_ref.clone();
+                    move |_self, event| {
+                        Inhibit(chess_board_ref.borrow().handle_key_pressed(event.get_keyval()))
+                    }
+                });
+
                 chess_board_ref.borrow().drawing_area.connect_event({
                     let chess_board_ref = chess_board_ref.clone();
                     move |_self, event| {
-                        let coords = event.get_coords().expect("Failed to get mouse coordinates !");
+                        let coords = match event.get_coords() {
+                            Some(coords) => coords,
+                            None => return Inhibit(false)
+                        };
+                        let (origin_x, origin_y) = chess_board_ref.borrow().board_origin.get();
+                        let coords = (coords.0 - origin_x, coords.1 - origin_y);
                         
+                        let right_button = event.get_button() == Some(3);
                         match event.get_event_type() {
+                            EventType::ButtonPress if right_button => {
+                                let modifiers = event.get_state().unwrap_or_else(ModifierType::empty);
+                                chess_board_ref.borrow().handle_annotation_started(coords, modifiers)
+                            },
+                            EventType::ButtonRelease if right_button =>
+                                chess_board_ref.borrow().handle_annotation_finished(coords),
                             EventType::ButtonPress => chess_board_ref.borrow().handle_mouse_pressed(coords),
                             EventType::ButtonRelease => chess_board_ref.borrow().handle_mouse_released(coords),
                             EventType::MotionNotify => chess_board_ref.borrow().handle_mouse_moved(coords),
@@ -165,354 +382,582 @@ impl ChessBoard
         }
     }
 
-    fn handle_mouse_pressed(&self, coords: (f64, f64)){
-        let cells_size = self.cells_size as f64;
-        let mut cell_coords = (
-            ((coords.0 - (cells_size * 0.5)) / cells_size) as i32,
-            7 - (((coords.1 - (cells_size * 0.5)) / cells_size) as i32),
-        );
-        if self.reversed {
-            cell_coords = (7-cell_coords.0, 7-cell_coords.1);
+    /// Fits the board, with its margins, in the given size and centers it.
+    fn handle_resize(&self, width: i32, height: i32)
+    {
+        self.allocated_size.set((width, height));
+        let geometry = BoardGeometry::fitting(width as f64, height as f64, self.margins_in_cells(), 10.0, self.reversed);
+        let cells_size = geometry.cells_size as u32;
+        self.board_origin.set((
+            ((width as f64 - geometry.board_width()) / 2.0).max(0.0).floor(),
+            ((height as f64 - geometry.board_height()) / 2.0).max(0.0).floor(),
+        ));
+
+        if cells_size != self.cells_size.get() {
+            self.cells_size.set(cells_size);
+            let pieces_size = (cells_size as f64 * 0.8) as u32;
+            self.pieces_images.replace(self.cached_pieces_images(pieces_size));
         }
+        self.drawing_area.queue_draw();
+    }
 
-        let (coords_x, coords_y) = coords;
-        let square: SQ = SQ::from((cell_coords.0 as u8) + 8* (cell_coords.1 as u8));
-        let piece = self.logic.borrow().piece_at_cell(square);
-        let piece_type = piece.type_of();
-        if piece_type != PieceType::None {
-            self.moved_piece.replace(Some(MovedPiece{
-                coords_x,
-                coords_y,
-                piece,
-                start_file: cell_coords.0 as u8,
-                start_rank: cell_coords.1 as u8,
-            }));
-
-            self.drawing_area.queue_draw();
+    /// The pieces images at the given size, loaded only if not among the last sizes used.
+    fn cached_pieces_images(&self, pieces_size: u32) -> HashMap<char, Pixbuf>
+    {
+        let mut cache = self.pieces_images_cache.borrow_mut();
+        let entry = match cache.iter().position(|(size, _)| *size == pieces_size) {
+            Some(index) => cache.remove(index),
+            None => (pieces_size, self.piece_set.borrow().load_images(pieces_size))
+        };
+        if cache.len() >= PIECES_IMAGES_CACHE_SIZES {
+            cache.remove(0);
         }
+        let pieces_images = entry.1.clone();
+        cache.push(entry);
+        pieces_images
     }
 
-    fn handle_mouse_released(&self, coords: (f64, f64)){
-        let cells_size = self.cells_size as f64;
-        let mut cell_coords = (
-            ((coords.0 - (cells_size * 0.5)) / cells_size) as u8,
-            7 - (((coords.1 - (cells_size * 0.5)) / cells_size) as u8),
-        );
-        if self.reversed {
-            cell_coords = ((7-cell_coords.0) as u8, (7-cell_coords.1) as u8);
+    /// The pixels and cells conversions for the current size and orientation.
+    fn geometry(&self) -> BoardGeometry
+    {
+        let cells_size = self.cells_size.get() as f64;
+        BoardGeometry::with_margins(cells_size, self.margins_in_cells().scaled(cells_size), self.reversed)
+    }
+
+    /// The side to move is always shown, the coordinates only if wanted outside the board.
+    fn margins_in_cells(&self) -> Margins
+    {
+        Margins::for_layout(self.coordinates.get().placement, true)
+    }
+
+    /// The cell under the given point of the board, None outside of the cells.
+    fn cell_at(&self, coords: (f64, f64)) -> Option<SQ>
+    {
+        self.geometry().cell_at(coords.0, coords.1)
+    }
+
+    /// Whether the user can pick up the piece on the given cell.
+    fn can_pick_up(&self, square: SQ) -> bool
+    {
+        let piece = self.displayed_pieces()[square.0 as usize];
+        let human_piece = match self.human_side.get() {
+            Some(side) => piece.player() == Some(side),
+            None => true
+        };
+        let side_to_move = if self.logic.borrow().is_white_turn() { Player::White } else { Player::Black };
+        let piece_to_move = piece.player() == Some(side_to_move) || self.premove_mode();
+        piece.type_of() != PieceType::None && human_piece && piece_to_move
+    }
+
+    /// Whether the moves entered now are premoves, played once the computer has answered.
+    fn premove_mode(&self) -> bool
+    {
+        self.human_side.get().is_some() && ! self.is_human_turn() && self.logic.borrow().get_result().is_none()
+    }
+
+    /// Plays the selected piece to the given cell, if it is one of its legal destinations,
+    /// or queues it as a premove while the computer is thinking.
+    fn move_selection_to(&self, square: SQ) -> bool
+    {
+        if let Some(selected_cell) = self.selected_cell.get() {
+            let possible_destination = if self.premove_mode() {
+                self.can_premove(selected_cell, square)
+            }
+            else {
+                self.legal_destinations.borrow().iter()
+                    .any(|(destination, _capture)| *destination == square)
+            };
+            if possible_destination {
+                self.clear_selection();
+                self.drop_piece(selected_cell, square);
+                return true;
+            }
         }
+        false
+    }
 
-        if let Some(moved_piece) = self.moved_piece.borrow().clone() {
-            let start_cell = SQ::from(moved_piece.start_file + 8 * moved_piece.start_rank);
-            let end_cell = SQ::from(cell_coords.0 + 8 * cell_coords.1);
+    /// Whether the piece shown on start_cell could go to end_cell, once the computer has answered.
+    fn can_premove(&self, start_cell: SQ, end_cell: SQ) -> bool
+    {
+        let piece = self.displayed_pieces()[start_cell.0 as usize];
+        ! self.can_pick_up(end_cell) && can_reach(piece, start_cell, end_cell)
+    }
 
-            if self.logic.borrow().is_legal_move(start_cell, end_cell) {
-                if self.logic.borrow().is_promotion_move(start_cell, end_cell) {
-                    let selected_role = self.open_promotion_selector();
-                    self.logic.borrow_mut().do_move(start_cell, end_cell, selected_role);
-                    self.drawing_area.queue_draw();
-                }
-                else {
-                    self.logic.borrow_mut().do_move(start_cell, end_cell, PieceType::None);
-                    self.drawing_area.queue_draw();
-                }
+    fn drop_piece(&self, start_cell: SQ, end_cell: SQ)
+    {
+        if self.premove_mode() {
+            if self.can_premove(start_cell, end_cell) {
+                self.premoves.borrow_mut().push((start_cell, end_cell));
             }
+            self.drawing_area.queue_draw();
         }
+        else {
+            let _ = self.submit_move(start_cell, end_cell);
+        }
+    }
 
-        self.moved_piece.replace(None);
+    pub fn cancel_premoves(&self)
+    {
+        self.premoves.borrow_mut().clear();
         self.drawing_area.queue_draw();
     }
 
-    fn handle_mouse_moved(&self, coords: (f64, f64)) {
-        match *self.moved_piece.borrow_mut() {
-            Some(ref mut moved_piece) => {
-                moved_piece.translate_to(coords.0, coords.1);
-                self.drawing_area.queue_draw();
-            }
-            None => {}
+    /// The pieces shown for each of the 64 cells : the ones of the current position,
+    /// moved by the queued premoves.
+    fn displayed_pieces(&self) -> Vec<Piece>
+    {
+        let mut pieces = self.pieces_snapshot();
+        self.premoves.borrow().iter().for_each(|(start_cell, end_cell)| {
+            pieces[end_cell.0 as usize] = pieces[start_cell.0 as usize];
+            pieces[start_cell.0 as usize] = Piece::None;
+        });
+        pieces
+    }
+
+    /// Plays the first queued premove if it is the user turn, discarding
+    /// all the premoves if it is not legal.
+    /// A promotion follows the auto-queen setting, as any move entered on the board.
+    fn play_premove(&self)
+    {
+        if self.human_side.get().is_none() || ! self.is_human_turn() || self.premoves.borrow().is_empty() {
+            return;
+        }
+        let (start_cell, end_cell) = self.premoves.borrow_mut().remove(0);
+        match self.submit_move(start_cell, end_cell) {
+            Ok(_) | Err(MoveError::MissingPromotion(_)) => {},
+            Err(e) => {
+                self.cancel_premoves();
+                let description = format!("Premove {}{} discarded : {}", start_cell, end_cell, e);
+                let handlers = self.premove_discarded_handlers.borrow().clone();
+                handlers.iter().for_each(|handler| handler(&description));
+            },
         }
     }
 
-    fn open_promotion_selector(&self) -> PieceType {
-        let dialog = Dialog::new();
-        dialog.set_title("Select your promotion piece");
-        dialog.set_modal(true);
+    /// The callback is given a message telling why the premove could not be played.
+    pub fn connect_premove_discarded<F: Fn(&str) + 'static>(&self, callback: F)
+    {
+        self.premove_discarded_handlers.borrow_mut().push(Rc::new(callback));
+    }
 
-        let dialog_ref = Rc::new(dialog);
-        let is_white_turn = self.logic.borrow().is_white_turn();
+    fn select_cell(&self, square: SQ)
+    {
+        self.selected_cell.set(Some(square));
+        self.legal_destinations.replace(self.logic.borrow().legal_destinations(square));
+        self.drawing_area.queue_draw();
+    }
 
-        let queen_image = if is_white_turn {
-            self.pieces_images.get(&'Q').expect("Failed to get white queen image")
-        } else {
-            self.pieces_images.get(&'q').expect("Failed to get black queen image")
-        };
-        let queen_image = Image::new_from_pixbuf(queen_image);
-
-        let queen_button = Button::new();
-        queen_button.set_image(&queen_image);
-        queen_button.connect_clicked({
-            let dialog_ref = dialog_ref.clone();
-            move |_button| {
-                dialog_ref.response(PromotionType::QUEEN as i32);
-                dialog_ref.close();
+    /// Handles the arrow keys moving the keyboard cursor, Enter or Space picking up or dropping
+    /// the piece under the cursor and Escape cancelling.
+    /// Gives whether the key has been used.
+    fn handle_key_pressed(&self, key: Key) -> bool
+    {
+        if self.promotion_picker.get().is_some() {
+            match key {
+                key::q | key::Q => self.finish_promotion(Some(PieceType::Q)),
+                key::r | key::R => self.finish_promotion(Some(PieceType::R)),
+                key::b | key::B => self.finish_promotion(Some(PieceType::B)),
+                key::n | key::N => self.finish_promotion(Some(PieceType::N)),
+                key::Escape => self.finish_promotion(None),
+                _ => {}
             }
-        });
+            return true;
+        }
 
-        let rook_image = if is_white_turn {
-            self.pieces_images.get(&'R').expect("Failed to get white rook image")
-        } else {
-            self.pieces_images.get(&'r').expect("Failed to get black rook image")
+        let cursor = self.cursor_cell.get();
+        let (file, rank) = (cursor.file_idx_of_sq() as i32, cursor.rank_idx_of_sq() as i32);
+        let direction = if self.reversed { -1 } else { 1 };
+        let moved_cursor = match key {
+            key::Up => Some((file, rank + direction)),
+            key::Down => Some((file, rank - direction)),
+            key::Left => Some((file - direction, rank)),
+            key::Right => Some((file + direction, rank)),
+            _ => None
         };
-        let rook_image = Image::new_from_pixbuf(rook_image);
-
-        let rook_button = Button::new();
-        rook_button.set_image(&rook_image);
-        rook_button.connect_clicked({
-            let dialog_ref = dialog_ref.clone();
-            move |_button| {
-                dialog_ref.response(PromotionType::ROOK as i32);
-                dialog_ref.close();
-            }
-        });
 
-        let bishop_image = if is_white_turn {
-            self.pieces_images.get(&'B').expect("Failed to get white bishop image")
-        } else {
-            self.pieces_images.get(&'b').expect("Failed to get black bishop image")
+        match (moved_cursor, key) {
+            (Some((file, rank)), _) => {
+                if (0..8).contains(&file) && (0..8).contains(&rank) {
+                    self.cursor_cell.set(SQ::from((file + 8 * rank) as u8));
+                }
+            },
+            (None, key::Return) | (None, key::KP_Enter) | (None, key::space) => {
+                if ! self.move_selection_to(cursor) {
+                    if self.can_pick_up(cursor) && self.selected_cell.get() != Some(cursor) {
+                        self.select_cell(cursor);
+                    }
+                    else {
+                        self.clear_selection();
+                    }
+                }
+            },
+            (None, key::Escape) => {
+                self.clear_selection();
+                self.cancel_premoves();
+            },
+            _ => return false
+        }
+
+        self.cursor_visible.set(true);
+        self.describe_cursor_cell();
+        self.drawing_area.queue_draw();
+        true
+    }
+
+    /// Describes the cell under the keyboard cursor for the screen readers,
+    /// without naming the pieces hidden by the blindfold mode.
+    fn describe_cursor_cell(&self)
+    {
+        let cursor = self.cursor_cell.get();
+        let piece = self.logic.borrow().piece_at_cell(cursor);
+        let piece_shown = match self.pieces_visibility() {
+            PiecesVisibility::Shown(opacity) => opacity > 0.0,
+            PiecesVisibility::HiddenSide(side) => piece.player() != Some(side),
+            PiecesVisibility::Hidden | PiecesVisibility::Discs => false,
         };
-        let bishop_image = Image::new_from_pixbuf(bishop_image);
-
-        let bishop_button = Button::new();
-        bishop_button.set_image(&bishop_image);
-        bishop_button.connect_clicked({
-            let dialog_ref = dialog_ref.clone();
-            move |_button| {
-                dialog_ref.response(PromotionType::BISHOP as i32);
-                dialog_ref.close();
-            }
-        });
+        let piece_text = if piece_shown { piece_description(piece) } else { String::from("hidden") };
+        let mut description = format!("{}, {}", cursor.to_string(), piece_text);
+        if self.selected_cell.get() == Some(cursor) {
+            description.push_str(", selected");
+        }
+        else if self.legal_destinations.borrow().iter().any(|(destination, _capture)| *destination == cursor) {
+            description.push_str(", legal destination");
+        }
+        self.set_accessible_description(&description);
+    }
 
-        let knight_image = if is_white_turn {
-            self.pieces_images.get(&'N').expect("Failed to get white knight image")
-        } else {
-            self.pieces_images.get(&'n').expect("Failed to get black knight image")
+    /// Sets the accessible description of the board, read by the screen readers but not shown.
+    fn set_accessible_description(&self, description: &str)
+    {
+        let description = match CString::new(description) {
+            Ok(description) => description,
+            Err(_) => return
         };
-        let knight_image = Image::new_from_pixbuf(knight_image);
-
-        let knight_button = Button::new();
-        knight_button.set_image(&knight_image);
-        knight_button.connect_clicked({
-            let dialog_ref = dialog_ref.clone();
-            move |_button| {
-                dialog_ref.response(PromotionType::KNIGHT as i32);
-                dialog_ref.close();
+        let widget: *mut gtk_sys::GtkWidget = self.drawing_area.upcast_ref::<Widget>().to_glib_none().0;
+        unsafe {
+            let accessible = gtk_sys::gtk_widget_get_accessible(widget);
+            if ! accessible.is_null() {
+                atk_object_set_description(accessible as *mut c_void, description.as_ptr());
             }
-        });
+        }
+    }
 
-        let buttons_box = GBox::new(Orientation::Horizontal, 10);
-        buttons_box.pack_start(
-            &queen_button,
-            true,
-            true,
-            0
-        );
-        buttons_box.pack_start(
-            &rook_button,
-            true,
-            true,
-            0
-        );
-        buttons_box.pack_start(
-            &bishop_button,
-            true,
-            true,
-            0
-        );
-        buttons_box.pack_start(
-            &knight_button,
-            true,
-            true,
-            0
-        );
+    fn handle_mouse_pressed(&self, coords: (f64, f64)){
+        self.drawing_area.grab_focus();
+        self.cursor_visible.set(false);
+        if let Some(picker) = self.promotion_picker.get() {
+            self.finish_promotion(picker.choice_at(self.cell_at(coords)));
+            return;
+        }
+        let square = match self.cell_at(coords) {
+            Some(square) => square,
+            None => {
+                self.clear_selection();
+                return;
+            }
+        };
+        self.cursor_cell.set(square);
 
-        dialog_ref.get_content_area().pack_start(
-            &buttons_box,
-            true,
-            true,
-            10
-        );
+        // Second click of a click-to-move input.
+        if self.move_selection_to(square) {
+            return;
+        }
 
-        dialog_ref.show_all();
+        let (coords_x, coords_y) = coords;
+        let piece = self.displayed_pieces()[square.0 as usize];
+        if self.can_pick_up(square) {
+            let already_selected = self.selected_cell.get() == Some(square);
+            self.select_cell(square);
+            self.moved_piece.replace(Some(MovedPiece{
+                coords_x,
+                coords_y,
+                piece,
+                start_file: square.file_idx_of_sq(),
+                start_rank: square.rank_idx_of_sq(),
+                already_selected,
+            }));
 
-        let response = dialog_ref.run();
-        if response == PromotionType::QUEEN as i32 { PieceType::Q }
-                        else if response == PromotionType::ROOK as i32 { PieceType::R }
-                        else if response == PromotionType::BISHOP as i32 { PieceType::B }
-                        else if response == PromotionType::KNIGHT as i32 { PieceType::N }
-                        else { PieceType::Q }
+            self.drawing_area.queue_draw();
+        }
+        else {
+            self.clear_selection();
+        }
+    }
 
+    fn handle_mouse_released(&self, coords: (f64, f64)){
+        let moved_piece = self.moved_piece.borrow().clone();
+        self.moved_piece.replace(None);
 
-    }
+        if let Some(moved_piece) = moved_piece {
+            let start_cell = SQ::from(moved_piece.start_file + 8 * moved_piece.start_rank);
+            match self.cell_at(coords) {
+                // A simple click : the piece stays selected, unless it was already.
+                Some(end_cell) if end_cell == start_cell => {
+                    if moved_piece.already_selected {
+                        self.clear_selection();
+                    }
+                },
+                Some(end_cell) => {
+                    self.clear_selection();
+                    self.drop_piece(start_cell, end_cell);
+                },
+                None => self.clear_selection()
+            }
+        }
 
-    fn paint(&self, cr: &Context){
-        self.draw_background(cr);
-        self.draw_cells(cr);
-        self.draw_pieces(cr);
-        self.draw_moved_piece(cr);
-        self.draw_coordinates(cr);
-        self.draw_player_turn(cr);
+        self.drawing_area.queue_draw();
     }
 
-    fn draw_background(&self, cr: &Context)
+    fn clear_selection(&self)
     {
-        let green_color = [60.0/255.0, 204.0/255.0, 100.0/255.0];
-        cr.set_source_rgb(
-            green_color[0],
-            green_color[1],
-            green_color[2],
-        );
-        cr.paint();
-    }
-
-    fn draw_cells(&self, cr: &Context)
-    {
-        (0..8).for_each(|rank| {
-            (0..8).for_each(|file| {
-                let white_cell_color = [255.0/255.0, 255.0/255.0, 179.0/255.0];
-                let black_cell_color = [153.0/255.0, 102.0/255.0, 51.0/255.0];
-
-                let is_white_cell = (file + rank) % 2 == 0;
-                let cell_color = if is_white_cell {white_cell_color} else {black_cell_color};
-
-                let rect_x = (self.cells_size as f64) * (0.5 + (file as f64));
-                let rect_y = (self.cells_size as f64) * (0.5 + (rank as f64));
-                let rect_size = self.cells_size as f64;
-
-                cr.rectangle(
-                    rect_x,
-                    rect_y,
-                    rect_size,
-                    rect_size,
-                );
-                cr.set_source_rgb(
-                    cell_color[0],
-                    cell_color[1],
-                    cell_color[2],
-                );
-                cr.fill();
-            });
-        });
+        self.selected_cell.set(None);
+        self.legal_destinations.replace(vec![]);
+        self.drawing_area.queue_draw();
     }
 
-    fn draw_pieces(&self, cr: &Context)
+    /// Plays a move entered on the board, either by dragging or by clicking.
+    /// A promotion is done with a queen if auto-queen is set, otherwise the piece is
+    /// asked on the board, the MissingPromotion error being given until the user chooses it.
+    fn submit_move(&self, start_cell: SQ, end_cell: SQ) -> Result<MoveOutcome, MoveError>
     {
-        (0..64).for_each(|index| {
-
-            let file = index % 8;
-            let rank = index / 8;
-                
-            let real_file = (if self.reversed { 7-file } else { file }) as u8;
-            let real_rank = (if self.reversed { 7-rank } else { rank }) as u8;
+        match self.commit_move(start_cell, end_cell, PieceType::None) {
+            Err(MoveError::MissingPromotion(_)) if self.auto_queen.get() => {
+                self.commit_move(start_cell, end_cell, PieceType::Q)
+            },
+            Err(MoveError::MissingPromotion(move_name)) => {
+                self.promotion_picker.set(Some(PromotionPicker::new(start_cell, end_cell)));
+                self.drawing_area.queue_draw();
+                Err(MoveError::MissingPromotion(move_name))
+            },
+            outcome => outcome
+        }
+    }
 
-            let piece = self.logic.borrow().piece_at_cell(SQ::from(real_file + 8 * real_rank));
-            
-            if piece != Piece::None {
-                let moved_piece = self.moved_piece.borrow().clone();
-                let not_moved_piece = 
-                    if moved_piece.is_none() { true }
-                    else {
-                        let moved_piece = moved_piece.expect("Failed to get moved piece !");
-                        moved_piece.start_file != real_file || moved_piece.start_rank != real_rank
-                    };
-
-                if not_moved_piece {
-                        let piece_char = piece.character().expect("Failed to get piece char value !");
-                        let image = self.pieces_images.get(&piece_char).expect("Failed to get piece image !");
-                        let location_x = (self.cells_size as f64) * (file as f64 + 0.5 + 0.1);
-                        let location_y = (self.cells_size as f64) * ((7.0-rank as f64) + 0.5 + 0.1);
-                        cr.set_source_pixbuf(
-                            &image,
-                            location_x,
-                            location_y
-                        );
-                        cr.paint();   
-                }
+    /// Plays the pending promotion with the given piece, or cancels it with None,
+    /// the premoves following the cancelled promotion being cancelled too.
+    fn finish_promotion(&self, promotion: Option<PieceType>)
+    {
+        if let Some(picker) = self.promotion_picker.replace(None) {
+            match promotion {
+                Some(promotion) => {
+                    let _ = self.commit_move(picker.start_cell, picker.end_cell, promotion);
+                },
+                None => self.cancel_premoves()
             }
-        });
+        }
+        self.drawing_area.queue_draw();
     }
 
-    fn draw_moved_piece(&self, cr: &Context)
+    /// Whether promotions are done with a queen without asking.
+    pub fn set_auto_queen(&self, auto_queen: bool)
     {
-        if let Some(moved_piece) = self.moved_piece.borrow().clone() {
-            let piece_pointer_x = moved_piece.coords_x - (self.cells_size as f64) * 0.4;
-            let piece_pointer_y = moved_piece.coords_y - (self.cells_size as f64) * 0.4;
-            let piece_char = moved_piece.piece.character().expect("Failed to get piece char value !");
-            let image = self.pieces_images.get(&piece_char).expect("Failed to get moved piece image !");
+        self.auto_queen.set(auto_queen);
+    }
 
-            cr.set_source_pixbuf(
-                &image,
-                piece_pointer_x,
-                piece_pointer_y
-            );
-            cr.paint(); 
+    pub fn play_typed_move(&self, move_text: &str) -> Result<MoveOutcome, MoveError>
+    {
+        if ! self.is_human_turn() {
+            return Err(MoveError::NotYourTurn);
         }
+        self.play_engine_move(move_text)
     }
 
-    fn draw_coordinates(&self, cr: &Context)
+    pub fn play_engine_move(&self, move_text: &str) -> Result<MoveOutcome, MoveError>
     {
-        let files = ["A", "B", "C", "D", "E", "F", "G", "H"];
-        let ranks = ["8", "7", "6", "5", "4", "3", "2", "1"];
-
-        cr.set_source_rgb(0.2, 0.4, 1.0);
-        cr.select_font_face(
-            "Sans Serif",
-            FontSlant::Normal,
-            FontWeight::Bold
-        );
-        cr.set_font_size((self.cells_size as f64) * 0.38);
-        
-        (0..8).for_each(|file_index| {
-            let real_file_index = if self.reversed { 7 - file_index } else { file_index };
+        let pieces_before = self.pieces_snapshot();
+        let outcome = self.logic.borrow_mut().do_typed_move(move_text);
+        if outcome.is_ok() {
+            self.animate_from(&pieces_before);
+        }
+        self.after_move_attempt(outcome)
+    }
 
-            let letter = files[file_index];
-            let letter_x = (self.cells_size as f64) * (0.9 + (real_file_index as f64));
-            let letter_y_top = (self.cells_size as f64) * 0.4;
-            let letter_y_bottom = (self.cells_size as f64) * 8.9;
+    pub fn connect_move_done<F: Fn(&MoveOutcome) + 'static>(&self, callback: F)
+    {
+        self.move_done_handlers.borrow_mut().push(Rc::new(callback));
+    }
 
-            cr.move_to(letter_x, letter_y_top);
-            cr.show_text(letter);
+    /// The callback is called after each move, history navigation or position loading.
+    pub fn connect_position_changed<F: Fn() + 'static>(&self, callback: F)
+    {
+        self.position_changed_handlers.borrow_mut().push(Rc::new(callback));
+    }
 
-            cr.move_to(letter_x, letter_y_bottom);
-            cr.show_text(letter);
-        });
+    fn notify_position_changed(&self)
+    {
+        self.restart_fading();
+        let handlers = self.position_changed_handlers.borrow().clone();
+        handlers.iter().for_each(|handler| handler());
+    }
 
-        (0..8).for_each(|rank_index| {
-            let real_rank_index = if self.reversed { 7 - rank_index } else { rank_index };
+    pub fn navigate(&self, target: Navigation)
+    {
+        let pieces_before = self.pieces_snapshot();
+        let changed = self.logic.borrow_mut().navigate(target);
+        if changed {
+            self.animate_from(&pieces_before);
+            self.premoves.borrow_mut().clear();
+            self.promotion_picker.set(None);
+            self.moved_piece.replace(None);
+            self.clear_selection();
+            self.notify_position_changed();
+        }
+    }
 
-            let letter = ranks[rank_index];
-            let letter_y = (self.cells_size as f64) * (1.2 + (real_rank_index as f64));
-            let letter_x_left = (self.cells_size as f64) * 0.1;
-            let letter_x_right = (self.cells_size as f64) * 8.6;
+    /// The pieces of the current position, for each of the 64 cells.
+    fn pieces_snapshot(&self) -> Vec<Piece>
+    {
+        self.logic.borrow().get_pieces()
+    }
 
-            cr.move_to(letter_x_left, letter_y);
-            cr.show_text(letter);
+    /// Slides the pieces from the given position to the current one.
+    fn animate_from(&self, pieces_before: &[Piece])
+    {
+        let animation = Animation::between(pieces_before, &self.pieces_snapshot());
+        self.animation.replace(animation);
+        if self.animation.borrow().is_none() || self.animation_ticking.get() {
+            return;
+        }
 
-            cr.move_to(letter_x_right, letter_y);
-            cr.show_text(letter);
+        self.animation_ticking.set(true);
+        self.drawing_area.add_tick_callback({
+            let animation = self.animation.clone();
+            let animation_ticking = self.animation_ticking.clone();
+            move |drawing_area, frame_clock| {
+                let running = match *animation.borrow_mut() {
+                    Some(ref mut animation) => animation.update(frame_clock.get_frame_time()),
+                    None => false
+                };
+                drawing_area.queue_draw();
+                if running {
+                    Continue(true)
+                }
+                else {
+                    animation.replace(None);
+                    animation_ticking.set(false);
+                    Continue(false)
+                }
+            }
         });
     }
 
-    fn draw_player_turn(&self, cr: &Context)
+    fn commit_move(&self, start_cell: SQ, end_cell: SQ, promotion: PieceType) -> Result<MoveOutcome, MoveError>
     {
-        let color = if self.logic.borrow().is_white_turn() { [1.0, 1.0, 1.0] } else { [0.0, 0.0, 0.0] };
-        let center = (self.cells_size as f64) * 8.75;
-        let radius = (self.cells_size as f64) * 0.25;
-        cr.arc(center, center, radius, 0.0, 2.0 * std::f64::consts::PI);
-        cr.set_source_rgb(
-            color[0],
-            color[1],
-            color[2],
+        let outcome = self.logic.borrow_mut().do_move(start_cell, end_cell, promotion);
+        self.after_move_attempt(outcome)
+    }
+
+    fn after_move_attempt(&self, outcome: Result<MoveOutcome, MoveError>) -> Result<MoveOutcome, MoveError>
+    {
+        if let Ok(ref move_outcome) = outcome {
+            self.clear_selection();
+            let handlers = self.move_done_handlers.borrow().clone();
+            handlers.iter().for_each(|handler| handler(move_outcome));
+            self.notify_position_changed();
+            self.play_premove();
+        }
+        outcome
+    }
+
+    /// Starts drawing an arrow or a circle, whose colour depends on the modifier keys :
+    /// green without modifier, red with Shift, blue with Alt and yellow with Ctrl.
+    fn handle_annotation_started(&self, coords: (f64, f64), modifiers: ModifierType)
+    {
+        let color = if modifiers.contains(ModifierType::SHIFT_MASK) { AnnotationColor::Red }
+            else if modifiers.contains(ModifierType::MOD1_MASK) { AnnotationColor::Blue }
+            else if modifiers.contains(ModifierType::CONTROL_MASK) { AnnotationColor::Yellow }
+            else { AnnotationColor::Green };
+        self.drawn_annotation.set(self.cell_at(coords).map(|cell| (cell, cell, color)));
+    }
+
+    fn handle_annotation_finished(&self, coords: (f64, f64))
+    {
+        if let (Some((start_cell, _, color)), Some(end_cell)) = (self.drawn_annotation.get(), self.cell_at(coords)) {
+            let annotation = if start_cell == end_cell {
+                Annotation::Circle(start_cell, color)
+            } else {
+                Annotation::Arrow(start_cell, end_cell, color)
+            };
+            self.logic.borrow_mut().toggle_annotation(annotation);
+        }
+        self.drawn_annotation.set(None);
+        self.drawing_area.queue_draw();
+    }
+
+    fn handle_mouse_moved(&self, coords: (f64, f64)) {
+        if let Some((start_cell, end_cell, color)) = self.drawn_annotation.get() {
+            let hovered_cell = self.cell_at(coords).unwrap_or(end_cell);
+            if hovered_cell != end_cell {
+                self.drawn_annotation.set(Some((start_cell, hovered_cell, color)));
+                self.drawing_area.queue_draw();
+            }
+            return;
+        }
+
+        if let Some(ref mut moved_piece) = *self.moved_piece.borrow_mut() {
+            moved_piece.translate_to(coords.0, coords.1);
+            self.drawing_area.queue_draw();
+        }
+    }
+
+    fn paint(&self, cr: &Context){
+        let (origin_x, origin_y) = self.board_origin.get();
+        cr.translate(origin_x, origin_y);
+
+        let theme = self.theme.borrow();
+        let pieces_images = self.pieces_images.borrow();
+        let logic = self.logic.borrow();
+        let mut painter = BoardPainter::new(
+            self.geometry(),
+            &theme,
+            &pieces_images,
+            self.displayed_pieces(),
+            logic.is_white_turn()
         );
-        cr.fill();
+        painter.pieces_visibility = self.pieces_visibility();
+        painter.coordinates = self.coordinates.get();
+        painter.last_move = logic.get_last_move().map(|last_move| (last_move.start_cell, last_move.end_cell));
+        painter.checked_king_cell = logic.checked_king_cell();
+        painter.premoves = self.premoves.borrow().clone();
+        painter.selected_cell = self.selected_cell.get();
+        painter.legal_destinations = self.legal_destinations.borrow().clone();
+        if self.cursor_visible.get() && self.drawing_area.has_focus() {
+            painter.cursor_cell = Some(self.cursor_cell.get());
+        }
+        painter.annotations = logic.get_annotations();
+        if let Some((start_cell, end_cell, color)) = self.drawn_annotation.get() {
+            painter.annotations.push(if start_cell == end_cell {
+                Annotation::Circle(start_cell, color)
+            } else {
+                Annotation::Arrow(start_cell, end_cell, color)
+            });
+        }
+        painter.dragged_piece = self.moved_piece.borrow().as_ref().map(|moved_piece| DraggedPiece {
+            piece: moved_piece.piece,
+            start_cell: SQ::from(moved_piece.start_file + 8 * moved_piece.start_rank),
+            x: moved_piece.coords_x,
+            y: moved_piece.coords_y,
+        });
+        painter.animation = self.animation.borrow().clone();
+        painter.promotion_picker = self.promotion_picker.get();
+        painter.paint(cr);
     }
+}
+
+fn piece_description(piece: Piece) -> String
+{
+    let colour = match piece.player() {
+        Some(Player::White) => "white",
+        Some(Player::Black) => "black",
+        None => return String::from("empty")
+    };
+    let name = match piece.type_of() {
+        PieceType::P => "pawn",
+        PieceType::N => "knight",
+        PieceType::B => "bishop",
+        PieceType::R => "rook",
+        PieceType::Q => "queen",
+        _ => "king",
+    };
+    format!("{} {}", colour, name)
 }
\ No newline at end of file
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs::File;
+use std::os::raw::c_char;
+use std::path::Path;
+use cairo::{Context, Format, ImageSurface, PDFSurface, Surface};
+use cairo::prelude::SurfaceExt;
+use cairo::enums::Status;
+use gdk_pixbuf::Pixbuf;
+use super::board_painter::BoardPainter;
+use super::geometry::{BoardGeometry, Margins};
+use super::piece_set::PieceSet;
+use super::theme::Theme;
+use super::super::logic::chessgame::ChessGame;
+use super::super::logic::coordinates::CoordinatesOptions;
+
+// The cairo bindings do not wrap the SVG surface, which is part of the cairo library all the same.
+extern "C" {
+    fn cairo_svg_surface_create(filename: *const c_char, width_in_points: f64, height_in_points: f64)
+        -> *mut cairo_sys::cairo_surface_t;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiagramFormat
+{
+    Png,
+    Svg,
+    Pdf,
+}
+
+pub const ALL_DIAGRAM_FORMATS: [DiagramFormat; 3] = [DiagramFormat::Png, DiagramFormat::Svg, DiagramFormat::Pdf];
+
+impl DiagramFormat
+{
+    pub fn label(&self) -> &'static str
+    {
+        match self {
+            DiagramFormat::Png => "PNG image",
+            DiagramFormat::Svg => "SVG drawing",
+            DiagramFormat::Pdf => "PDF document",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str
+    {
+        match self {
+            DiagramFormat::Png => "png",
+            DiagramFormat::Svg => "svg",
+            DiagramFormat::Pdf => "pdf",
+        }
+    }
+
+    /// The format matching the extension of the given file, if any.
+    pub fn from_path(path: &Path) -> Option<DiagramFormat>
+    {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        ALL_DIAGRAM_FORMATS.iter().cloned().find(|format| format.extension() == extension)
+    }
+}
+
+/// What a diagram shows, and its size in pixels (points for SVG and PDF), margins included :
+/// the diagram is as big as possible within this size, with whole cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DiagramOptions
+{
+    pub size: u32,
+    pub reversed: bool,
+    pub coordinates: CoordinatesOptions,
+    pub player_turn: bool,
+    /// The last move, the king in check, and the arrows and circles of the position.
+    pub highlights: bool,
+}
+
+impl Default for DiagramOptions
+{
+    fn default() -> DiagramOptions
+    {
+        DiagramOptions {
+            size: 400,
+            reversed: false,
+            coordinates: CoordinatesOptions::default(),
+            player_turn: true,
+            highlights: true,
+        }
+    }
+}
+
+impl DiagramOptions
+{
+    /// The board fills the diagram, the margins holding only what is shown in them.
+    pub fn geometry(&self) -> BoardGeometry
+    {
+        let margins = Margins::for_layout(self.coordinates.placement, self.player_turn);
+        BoardGeometry::fitting(self.size as f64, self.size as f64, margins, 1.0, self.reversed)
+    }
+
+    /// The size of the pieces images to load for the diagram.
+    pub fn pieces_size(&self) -> u32
+    {
+        (self.geometry().cells_size * 0.8) as u32
+    }
+}
+
+/// A painter for the current position of the game, as a diagram with the given options.
+pub fn diagram_painter<'a>(game: &ChessGame, options: &DiagramOptions, theme: &'a Theme,
+    pieces_images: &'a HashMap<char, Pixbuf>) -> BoardPainter<'a>
+{
+    let mut painter = BoardPainter::new(options.geometry(), theme, pieces_images, game.get_pieces(), game.is_white_turn());
+    painter.coordinates = options.coordinates;
+    painter.show_player_turn = options.player_turn;
+    if options.highlights {
+        painter.last_move = game.get_last_move().map(|last_move| (last_move.start_cell, last_move.end_cell));
+        painter.checked_king_cell = game.checked_king_cell();
+        painter.annotations = game.get_annotations();
+    }
+    painter
+}
+
+/// Writes the current position of the game as a diagram file.
+pub fn export_diagram(game: &ChessGame, options: &DiagramOptions, theme: &Theme, piece_set: &PieceSet,
+    format: DiagramFormat, path: &Path) -> Result<(), String>
+{
+    let pieces_images = piece_set.load_images(options.pieces_size());
+    let painter = diagram_painter(game, options, theme, &pieces_images);
+    let (width, height) = (painter.geometry.board_width(), painter.geometry.board_height());
+
+    match format {
+        DiagramFormat::Png => {
+            let surface = ImageSurface::create(Format::ARgb32, width as i32, height as i32)
+                .map_err(|e| format!("Could not create the image ({:?})", e))?;
+            painter.paint(&Context::new(&surface));
+            let mut file = File::create(path)
+                .map_err(|e| format!("Could not create {} ({})", path.display(), e))?;
+            surface.write_to_png(&mut file)
+                .map_err(|e| format!("Could not write {} ({:?})", path.display(), e))
+        },
+        DiagramFormat::Svg => {
+            let file_name = CString::new(path.to_string_lossy().into_owned())
+                .map_err(|_e| format!("Bad file name {} !", path.display()))?;
+            let surface = unsafe {
+                Surface::from_raw_full(cairo_svg_surface_create(file_name.as_ptr(), width, height))
+            };
+            paint_and_finish(&painter, &surface, path)
+        },
+        DiagramFormat::Pdf => {
+            let surface = PDFSurface::create(path, width, height);
+            paint_and_finish(&painter, &surface, path)
+        },
+    }
+}
+
+/// Paints on a surface writing its file as it is finished.
+fn paint_and_finish<S: AsRef<Surface>>(painter: &BoardPainter, surface: &S, path: &Path) -> Result<(), String>
+{
+    painter.paint(&Context::new(surface));
+    surface.finish();
+    match surface.status() {
+        Status::Success => Ok(()),
+        status => Err(format!("Could not write {} ({:?})", path.display(), status))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,88 @@
+use gtk::prelude::*;
+use gtk::{Dialog, Window, Grid, Label, SpinButton, ComboBoxText, CheckButton, ResponseType};
+use glib::translate::ToGlib;
+use super::diagram::{DiagramOptions, DiagramFormat, ALL_DIAGRAM_FORMATS};
+use super::super::logic::coordinates::ALL_COORDINATES_PLACEMENTS;
+
+/// Asks for the format of the diagram and what it shows.
+/// Gives None if the user cancelled.
+pub fn ask_diagram_options(parent: &Window, options: &DiagramOptions, format: DiagramFormat)
+    -> Option<(DiagramOptions, DiagramFormat)>
+{
+    let dialog = Dialog::new();
+    dialog.set_title("Export diagram");
+    dialog.set_modal(true);
+    dialog.set_transient_for(parent);
+    dialog.add_button("Cancel", ResponseType::Cancel.to_glib());
+    dialog.add_button("Export", ResponseType::Ok.to_glib());
+
+    let grid = Grid::new();
+    grid.set_row_spacing(10);
+    grid.set_column_spacing(10);
+
+    let format_combo = ComboBoxText::new();
+    ALL_DIAGRAM_FORMATS.iter().enumerate().for_each(|(index, diagram_format)| {
+        format_combo.append_text(diagram_format.label());
+        if *diagram_format == format {
+            format_combo.set_active(index as i32);
+        }
+    });
+
+    let size_button = SpinButton::new_with_range(50.0, 4000.0, 10.0);
+    size_button.set_value(options.size as f64);
+
+    let reversed_button = CheckButton::new_with_label("Black at the bottom");
+    reversed_button.set_active(options.reversed);
+    let coordinates_combo = ComboBoxText::new();
+    ALL_COORDINATES_PLACEMENTS.iter().enumerate().for_each(|(index, placement)| {
+        coordinates_combo.append_text(placement.label());
+        if *placement == options.coordinates.placement {
+            coordinates_combo.set_active(index as i32);
+        }
+    });
+    let player_turn_button = CheckButton::new_with_label("Side to move");
+    player_turn_button.set_active(options.player_turn);
+    let highlights_button = CheckButton::new_with_label("Last move, check, arrows and circles");
+    highlights_button.set_active(options.highlights);
+
+    grid.attach(&Label::new("Format"), 0, 0, 1, 1);
+    grid.attach(&format_combo, 1, 0, 1, 1);
+    grid.attach(&Label::new("Size"), 0, 1, 1, 1);
+    grid.attach(&size_button, 1, 1, 1, 1);
+    grid.attach(&reversed_button, 1, 2, 1, 1);
+    grid.attach(&Label::new("Coordinates"), 0, 3, 1, 1);
+    grid.attach(&coordinates_combo, 1, 3, 1, 1);
+    grid.attach(&player_turn_button, 1, 4, 1, 1);
+    grid.attach(&highlights_button, 1, 5, 1, 1);
+
+    dialog.get_content_area().pack_start(
+        &grid,
+        true,
+        true,
+        10
+    );
+    dialog.show_all();
+
+    let response = dialog.run();
+    let choice = if response == ResponseType::Ok.to_glib() {
+        let mut coordinates = options.coordinates;
+        if let Some(placement) = ALL_COORDINATES_PLACEMENTS.get(coordinates_combo.get_active() as usize) {
+            coordinates.placement = *placement;
+        }
+        let new_options = DiagramOptions {
+            size: size_button.get_value_as_int() as u32,
+            reversed: reversed_button.get_active(),
+            coordinates,
+            player_turn: player_turn_button.get_active(),
+            highlights: highlights_button.get_active(),
+        };
+        let new_format = ALL_DIAGRAM_FORMATS.get(format_combo.get_active() as usize)
+            .cloned()
+            .unwrap_or(format);
+        Some((new_options, new_format))
+    }
+    else { None };
+
+    dialog.destroy();
+    choice
+}
\ No newline at end of file
@@ -0,0 +1,251 @@
+use pleco::core::sq::{SQ};
+use super::super::logic::coordinates::CoordinatesPlacement;
+
+/// The space around the cells, on each side.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Margins
+{
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+impl Margins
+{
+    pub fn uniform(margin: f64) -> Margins
+    {
+        Margins {
+            left: margin,
+            top: margin,
+            right: margin,
+            bottom: margin,
+        }
+    }
+
+    /// The margins, in cells, holding the coordinates written outside of the board
+    /// and the player turn, which goes in the right margin.
+    pub fn for_layout(placement: CoordinatesPlacement, player_turn: bool) -> Margins
+    {
+        let turn_margin = if player_turn { 0.5 } else { 0.0 };
+        match placement {
+            CoordinatesPlacement::OutsideAllSides => Margins::uniform(0.5),
+            CoordinatesPlacement::OutsideBottomLeft => Margins {
+                left: 0.5,
+                top: 0.0,
+                right: turn_margin,
+                bottom: 0.5,
+            },
+            CoordinatesPlacement::Off | CoordinatesPlacement::InsideCorners => Margins {
+                left: 0.0,
+                top: 0.0,
+                right: turn_margin,
+                bottom: 0.0,
+            },
+        }
+    }
+
+    /// The margins in pixels, from margins given in cells.
+    pub fn scaled(&self, factor: f64) -> Margins
+    {
+        Margins {
+            left: self.left * factor,
+            top: self.top * factor,
+            right: self.right * factor,
+            bottom: self.bottom * factor,
+        }
+    }
+}
+
+/// Converts between the board pixels and the cells.
+/// The pixels are counted from the top left corner of the board margins,
+/// the cells starting after the left and top margins.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardGeometry
+{
+    pub cells_size: f64,
+    pub margins: Margins,
+    pub reversed: bool,
+}
+
+impl BoardGeometry
+{
+    pub fn with_margins(cells_size: f64, margins: Margins, reversed: bool) -> BoardGeometry
+    {
+        BoardGeometry {
+            cells_size,
+            margins,
+            reversed,
+        }
+    }
+
+    /// The biggest cells size, at least the given minimum, so that the board fits
+    /// in the given size, with margins given in cells.
+    pub fn fitting(width: f64, height: f64, margins_in_cells: Margins, minimum_cells_size: f64,
+        reversed: bool) -> BoardGeometry
+    {
+        let cells_size = (width / (8.0 + margins_in_cells.left + margins_in_cells.right))
+            .min(height / (8.0 + margins_in_cells.top + margins_in_cells.bottom))
+            .floor()
+            .max(minimum_cells_size);
+        BoardGeometry::with_margins(cells_size, margins_in_cells.scaled(cells_size), reversed)
+    }
+
+    /// The cell under the given point, None outside of the cells (margins included).
+    pub fn cell_at(&self, x: f64, y: f64) -> Option<SQ>
+    {
+        if self.cells_size <= 0.0 {
+            return None;
+        }
+        let column = ((x - self.margins.left) / self.cells_size).floor();
+        let line = ((y - self.margins.top) / self.cells_size).floor();
+        if ! (0.0..8.0).contains(&column) || ! (0.0..8.0).contains(&line) {
+            return None;
+        }
+
+        let (column, line) = (column as u8, line as u8);
+        let (file, rank) = if self.reversed {
+            (7 - column, line)
+        } else {
+            (column, 7 - line)
+        };
+        Some(SQ::from(file + 8 * rank))
+    }
+
+    /// The top left corner of the given cell.
+    pub fn cell_origin(&self, cell: SQ) -> (f64, f64)
+    {
+        let file = cell.file_idx_of_sq() as f64;
+        let rank = cell.rank_idx_of_sq() as f64;
+        let column = if self.reversed { 7.0 - file } else { file };
+        let line = if self.reversed { rank } else { 7.0 - rank };
+        (self.margins.left + self.cells_size * column, self.margins.top + self.cells_size * line)
+    }
+
+    pub fn cell_center(&self, cell: SQ) -> (f64, f64)
+    {
+        let (x, y) = self.cell_origin(cell);
+        (x + self.cells_size * 0.5, y + self.cells_size * 0.5)
+    }
+
+    /// The width of the board, margins included.
+    pub fn board_width(&self) -> f64
+    {
+        self.cells_size * 8.0 + self.margins.left + self.margins.right
+    }
+
+    /// The height of the board, margins included.
+    pub fn board_height(&self) -> f64
+    {
+        self.cells_size * 8.0 + self.margins.top + self.margins.bottom
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::super::super::logic::test_helpers::cell;
+
+    #[test]
+    fn corners_in_both_orientations()
+    {
+        let white_side = BoardGeometry::with_margins(50.0, Margins::uniform(25.0), false);
+        assert_eq!(white_side.cell_at(26.0, 26.0), Some(cell("a8")));
+        assert_eq!(white_side.cell_at(424.0, 424.0), Some(cell("h1")));
+        assert_eq!(white_side.cell_at(26.0, 424.0), Some(cell("a1")));
+
+        let black_side = BoardGeometry::with_margins(50.0, Margins::uniform(25.0), true);
+        assert_eq!(black_side.cell_at(26.0, 26.0), Some(cell("h1")));
+        assert_eq!(black_side.cell_at(424.0, 424.0), Some(cell("a8")));
+        assert_eq!(black_side.cell_at(26.0, 424.0), Some(cell("h8")));
+    }
+
+    #[test]
+    fn cells_borders_belong_to_the_next_cell()
+    {
+        let geometry = BoardGeometry::with_margins(40.0, Margins::uniform(0.0), false);
+        assert_eq!(geometry.cell_at(0.0, 0.0), Some(cell("a8")));
+        assert_eq!(geometry.cell_at(39.99, 0.0), Some(cell("a8")));
+        assert_eq!(geometry.cell_at(40.0, 0.0), Some(cell("b8")));
+        assert_eq!(geometry.cell_at(319.99, 319.99), Some(cell("h1")));
+    }
+
+    #[test]
+    fn margins_and_outside_are_off_board()
+    {
+        let geometry = BoardGeometry::with_margins(50.0, Margins::uniform(25.0), false);
+        assert_eq!(geometry.cell_at(10.0, 200.0), None);
+        assert_eq!(geometry.cell_at(200.0, 10.0), None);
+        assert_eq!(geometry.cell_at(430.0, 200.0), None);
+        assert_eq!(geometry.cell_at(200.0, 440.0), None);
+        assert_eq!(geometry.cell_at(-5.0, -5.0), None);
+        assert_eq!(geometry.cell_at(-500.0, 200.0), None);
+        assert_eq!(geometry.cell_at(5000.0, 200.0), None);
+    }
+
+    #[test]
+    fn empty_board_has_no_cell()
+    {
+        let geometry = BoardGeometry::with_margins(0.0, Margins::uniform(0.0), false);
+        assert_eq!(geometry.cell_at(0.0, 0.0), None);
+    }
+
+    #[test]
+    fn cell_centers_map_back_to_their_cell()
+    {
+        [(50.0, 25.0), (13.0, 0.0), (97.5, 40.0)].iter().for_each(|(cells_size, margin)| {
+            [false, true].iter().for_each(|reversed| {
+                let geometry = BoardGeometry::with_margins(*cells_size, Margins::uniform(*margin), *reversed);
+                (0..64u8).for_each(|index| {
+                    let (x, y) = geometry.cell_center(SQ::from(index));
+                    assert_eq!(geometry.cell_at(x, y), Some(SQ::from(index)));
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn cell_origins_follow_the_orientation()
+    {
+        let white_side = BoardGeometry::with_margins(50.0, Margins::uniform(25.0), false);
+        assert_eq!(white_side.cell_origin(cell("a8")), (25.0, 25.0));
+        assert_eq!(white_side.cell_origin(cell("e2")), (225.0, 325.0));
+
+        let black_side = BoardGeometry::with_margins(50.0, Margins::uniform(25.0), true);
+        assert_eq!(black_side.cell_origin(cell("h1")), (25.0, 25.0));
+        assert_eq!(black_side.cell_origin(cell("e2")), (175.0, 75.0));
+        assert_eq!((black_side.board_width(), black_side.board_height()), (450.0, 450.0));
+    }
+
+    #[test]
+    fn uneven_margins_shift_the_cells()
+    {
+        let margins = Margins { left: 20.0, top: 0.0, right: 0.0, bottom: 20.0 };
+        let geometry = BoardGeometry::with_margins(40.0, margins, false);
+        assert_eq!(geometry.cell_origin(cell("a8")), (20.0, 0.0));
+        assert_eq!(geometry.cell_at(21.0, 1.0), Some(cell("a8")));
+        assert_eq!(geometry.cell_at(21.0, 330.0), None);
+        assert_eq!(geometry.cell_at(10.0, 10.0), None);
+        assert_eq!((geometry.board_width(), geometry.board_height()), (340.0, 340.0));
+    }
+
+    #[test]
+    fn fitting_uses_the_space_left_by_the_margins()
+    {
+        let all_sides = BoardGeometry::fitting(450.0, 600.0,
+            Margins::for_layout(CoordinatesPlacement::OutsideAllSides, true), 10.0, false);
+        assert_eq!(all_sides.cells_size, 50.0);
+        assert_eq!(all_sides.margins, Margins::uniform(25.0));
+
+        let hidden = BoardGeometry::fitting(450.0, 600.0,
+            Margins::for_layout(CoordinatesPlacement::Off, false), 10.0, false);
+        assert_eq!(hidden.cells_size, 56.0);
+        assert_eq!(hidden.margins, Margins::uniform(0.0));
+
+        let tiny = BoardGeometry::fitting(30.0, 30.0,
+            Margins::for_layout(CoordinatesPlacement::InsideCorners, true), 10.0, false);
+        assert_eq!(tiny.cells_size, 10.0);
+        assert_eq!(tiny.margins.right, 5.0);
+    }
+}
\ No newline at end of file
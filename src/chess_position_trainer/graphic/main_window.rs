@@ -1,36 +1,130 @@
+use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::fs;
 use gtk::prelude::*;
-use gtk::{Window, WindowType, Button, Image, Box as GtkBox, Orientation};
+use gtk::{Window, WindowType, Button, Image, Box as GtkBox, Orientation, Entry, Label,
+    FileChooserDialog, FileChooserAction, ResponseType, ComboBoxText};
 use gdk_pixbuf::Pixbuf;
 use gio::MemoryInputStream;
 use glib::Bytes;
+use glib::translate::ToGlib;
 use super::super::graphic::{ChessBoard, load_image};
+use super::super::graphic::preferences_dialog::edit_preferences;
+use super::super::graphic::new_game_dialog::ask_new_game;
+use super::super::graphic::analysis_panel::AnalysisPanel;
+use super::super::graphic::theme::theme_by_name;
+use super::super::graphic::piece_set::piece_set_by_name;
+use super::super::graphic::diagram::{DiagramOptions, DiagramFormat, export_diagram};
+use super::super::graphic::export_diagram_dialog::ask_diagram_options;
+use super::super::graphic::review_grade_dialog::ask_grade;
+use super::super::logic::engine::{EngineOpponent, EngineReply, EngineScore, SearchPurpose};
+use super::super::logic::preferences::Preferences;
+use super::super::logic::side_choice::SideChoice;
+use super::super::logic::training_goal::{TrainingGoal, GoalStatus};
+use super::super::logic::tablebase::{TablebaseAnswer, TablebaseCache};
+use super::super::logic::position_library::PositionLibrary;
+use super::super::logic::spaced_repetition::{Grade, SUCCESS_GRADES, today};
+use super::super::logic::variation_tree::Navigation;
+use pleco::core::{Player};
 
+/// The engine finds the tablebase answer at once, without searching.
+const TABLEBASE_PROBE_MOVETIME_MS: u32 = 100;
+
+/// Training : the user plays one side against the computer and the game result counts.
+/// Analysis : the user moves both sides, helped by the engine evaluation and the variation tree.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AppMode
+{
+    Training,
+    Analysis,
+}
+
+#[derive(Clone)]
 pub struct MainWindow
 {
     window: Window,
+    chessboard: Rc<RefCell<ChessBoard>>,
+    status_label: Label,
+    material_label: Label,
+    goal_label: Label,
+    preferences: Rc<RefCell<Preferences>>,
+    engine: Rc<RefCell<Option<EngineOpponent>>>,
+    side_choice: Rc<Cell<SideChoice>>,
+    goal: Rc<Cell<TrainingGoal>>,
+    /// The tablebase answers of the engine, asked by the tablebase win goal.
+    tablebase: Rc<RefCell<TablebaseCache>>,
+    user_side: Rc<Cell<Player>>,
+    mode: Rc<Cell<AppMode>>,
+    mode_combo: ComboBoxText,
+    library: Rc<RefCell<PositionLibrary>>,
+    /// The library positions left in the review session.
+    review_queue: Rc<RefCell<Vec<usize>>>,
+    /// The library position being reviewed, until its goal is decided.
+    reviewed_position: Rc<Cell<Option<usize>>>,
+    analysis_panel: AnalysisPanel,
+    game_actions: GtkBox,
+    /// The game actions only available while training.
+    resign_button: Button,
+    claim_draw_button: Button,
+    offer_draw_button: Button,
+    diagram_options: Rc<Cell<DiagramOptions>>,
+    diagram_format: Rc<Cell<DiagramFormat>>,
 }
 
 impl MainWindow
 {
     pub fn new() -> MainWindow
     {
-        let mut main_window = MainWindow{window: Window::new(WindowType::Toplevel)};
+        let chessboard = ChessBoard::new_from_default()
+            .expect("Failed to intialize the chessboard !");
+
+        let main_window = MainWindow{
+            window: Window::new(WindowType::Toplevel),
+            chessboard,
+            status_label: Label::new(None),
+            material_label: Label::new(None),
+            goal_label: Label::new(None),
+            preferences: Rc::new(RefCell::new(Preferences::load())),
+            engine: Rc::new(RefCell::new(None)),
+            side_choice: Rc::new(Cell::new(SideChoice::SideToMove)),
+            goal: Rc::new(Cell::new(TrainingGoal::Win)),
+            tablebase: Rc::new(RefCell::new(TablebaseCache::default())),
+            user_side: Rc::new(Cell::new(Player::White)),
+            mode: Rc::new(Cell::new(AppMode::Training)),
+            mode_combo: ComboBoxText::new(),
+            library: Rc::new(RefCell::new(PositionLibrary::load())),
+            review_queue: Rc::new(RefCell::new(vec![])),
+            reviewed_position: Rc::new(Cell::new(None)),
+            analysis_panel: AnalysisPanel::new(),
+            game_actions: GtkBox::new(Orientation::Horizontal, 5),
+            resign_button: Button::new_with_label("Resign"),
+            claim_draw_button: Button::new_with_label("Claim draw"),
+            offer_draw_button: Button::new_with_label("Offer draw"),
+            diagram_options: Rc::new(Cell::new(DiagramOptions::default())),
+            diagram_format: Rc::new(Cell::new(DiagramFormat::Png)),
+        };
         main_window.initialize();
+        main_window.start_session(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            SideChoice::SideToMove,
+            TrainingGoal::Win,
+        ).expect("Failed to start the default game !");
         main_window
     }
 
     pub fn show(&self)
     {
         self.window.show_all();
+        self.apply_mode();
     }
 
-    fn initialize(&mut self)
+    fn initialize(&self)
     {
         self.set_size_and_title();
         self.set_icon();
+        self.apply_board_preferences();
 
-        let chessboard = ChessBoard::new_from_default()
-            .expect("Failed to intialize the chessboard !");
+        let chessboard = self.chessboard.clone();
 
         let reverse_board_button = Button::new();
         let reverse_board_button_image = Image::new_from_pixbuf(
@@ -48,10 +142,70 @@ impl MainWindow
             }
         });
 
+        let new_game_button = Button::new_with_label("New game");
+        new_game_button.connect_clicked({
+            let main_window = self.clone();
+            move |_button| {
+                main_window.ask_new_game();
+            }
+        });
+
+        let add_to_library_button = Button::new_with_label("Add to library");
+        add_to_library_button.connect_clicked({
+            let main_window = self.clone();
+            move |_button| {
+                main_window.add_to_library();
+            }
+        });
+
+        let review_button = Button::new_with_label("Review due positions");
+        review_button.connect_clicked({
+            let main_window = self.clone();
+            move |_button| {
+                main_window.review_due_positions();
+            }
+        });
+
+        let mode_combo = &self.mode_combo;
+        mode_combo.append_text("Training");
+        mode_combo.append_text("Analysis");
+        mode_combo.set_active(0);
+        mode_combo.connect_changed({
+            let main_window = self.clone();
+            move |combo| {
+                let mode = if combo.get_active() == 1 { AppMode::Analysis } else { AppMode::Training };
+                main_window.set_mode(mode);
+            }
+        });
+
         let buttons_hbox = GtkBox::new(
             Orientation::Horizontal,
             20,
         );
+        buttons_hbox.pack_start(
+            &new_game_button,
+            true,
+            false,
+            10,
+        );
+        buttons_hbox.pack_start(
+            &add_to_library_button,
+            true,
+            false,
+            10,
+        );
+        buttons_hbox.pack_start(
+            &review_button,
+            true,
+            false,
+            10,
+        );
+        buttons_hbox.pack_start(
+            mode_combo,
+            true,
+            false,
+            10,
+        );
         buttons_hbox.pack_start(
             &reverse_board_button,
             true,
@@ -59,6 +213,33 @@ impl MainWindow
             10,
         );
 
+        self.build_game_actions();
+        let navigation_hbox = self.build_navigation_buttons();
+
+        self.analysis_panel.connect_node_activated({
+            let chessboard = chessboard.clone();
+            move |node| {
+                chessboard.borrow().navigate(Navigation::Node(node));
+            }
+        });
+
+        let board_hbox = GtkBox::new(
+            Orientation::Horizontal,
+            5,
+        );
+        board_hbox.pack_start(
+            chessboard.borrow().get_drawing_area(),
+            true,
+            true,
+            0,
+        );
+        board_hbox.pack_start(
+            self.analysis_panel.get_widget(),
+            false,
+            false,
+            5,
+        );
+
         let window_vbox = GtkBox::new(
             Orientation::Vertical,
             0,
@@ -70,27 +251,719 @@ impl MainWindow
             10,
         );
         window_vbox.pack_start(
-            chessboard.borrow().get_drawing_area(),
+            &board_hbox,
             true,
             true,
             0,
         );
+        window_vbox.pack_start(
+            &self.material_label,
+            false,
+            false,
+            0,
+        );
+        window_vbox.pack_start(
+            &self.goal_label,
+            false,
+            false,
+            0,
+        );
+        window_vbox.pack_start(
+            &navigation_hbox,
+            false,
+            false,
+            5,
+        );
+
+        let move_entry = Entry::new();
+        move_entry.set_placeholder_text("Type a move (Nf3, exd8=Q+, O-O, e7e8q) then press Enter");
+        let move_feedback_label = Label::new(None);
+
+        move_entry.connect_activate({
+            let chessboard = chessboard.clone();
+            let move_feedback_label = move_feedback_label.clone();
+            move |entry| {
+                let move_text = entry.get_text().unwrap_or_default();
+                match chessboard.borrow().play_typed_move(&move_text) {
+                    Ok(_) => entry.set_text(""),
+                    Err(e) => move_feedback_label.set_text(&format!("{}", e))
+                }
+            }
+        });
+
+        chessboard.borrow().connect_move_done({
+            let move_feedback_label = move_feedback_label.clone();
+            move |outcome| {
+                let mut feedback = format!("Last move : {}", outcome.san);
+                if outcome.gives_mate {
+                    feedback.push_str(" - Checkmate !");
+                }
+                move_feedback_label.set_text(&feedback);
+            }
+        });
+
+        chessboard.borrow().connect_premove_discarded({
+            let move_feedback_label = move_feedback_label.clone();
+            move |message| {
+                move_feedback_label.set_text(message);
+            }
+        });
+
+        chessboard.borrow().connect_position_changed({
+            let main_window = self.clone();
+            move || {
+                main_window.update_status();
+                main_window.refresh_analysis();
+                if main_window.mode.get() == AppMode::Training {
+                    main_window.let_computer_play_if_needed();
+                }
+            }
+        });
+
+        window_vbox.pack_start(
+            &move_entry,
+            false,
+            false,
+            5,
+        );
+        window_vbox.pack_start(
+            &move_feedback_label,
+            false,
+            false,
+            5,
+        );
+        window_vbox.pack_start(
+            &self.game_actions,
+            false,
+            false,
+            5,
+        );
+        window_vbox.pack_start(
+            &self.status_label,
+            false,
+            false,
+            5,
+        );
 
         self.window.add(&window_vbox);
-        
+
+        gtk::timeout_add(100, {
+            let main_window = self.clone();
+            move || {
+                main_window.poll_engine();
+                Continue(true)
+            }
+        });
+
         self.window.connect_delete_event(|_, _| {
             gtk::main_quit();
             Inhibit(false)
         });
     }
 
-    fn set_size_and_title(&mut self){
+    fn build_navigation_buttons(&self) -> GtkBox
+    {
+        let navigation_hbox = GtkBox::new(
+            Orientation::Horizontal,
+            5,
+        );
+        let buttons = [
+            ("|<", Navigation::Start),
+            ("<", Navigation::Back),
+            (">", Navigation::Forward),
+            (">|", Navigation::End),
+        ];
+        buttons.iter().for_each(|(label, target)| {
+            let button = Button::new_with_label(label);
+            button.connect_clicked({
+                let chessboard = self.chessboard.clone();
+                let target = *target;
+                move |_button| {
+                    chessboard.borrow().navigate(target);
+                }
+            });
+            navigation_hbox.pack_start(
+                &button,
+                true,
+                true,
+                0,
+            );
+        });
+
+        navigation_hbox
+    }
+
+    fn build_game_actions(&self)
+    {
+        self.resign_button.connect_clicked({
+            let main_window = self.clone();
+            move |_button| {
+                let white_resigns = match main_window.chessboard.borrow().get_human_side() {
+                    Some(side) => side == Player::White,
+                    None => main_window.chessboard.borrow().get_game().is_white_turn()
+                };
+                main_window.chessboard.borrow().resign(white_resigns);
+                main_window.update_status();
+            }
+        });
+
+        self.claim_draw_button.connect_clicked({
+            let main_window = self.clone();
+            move |_button| {
+                let claim = main_window.chessboard.borrow().claim_draw();
+                match claim {
+                    Ok(_) => main_window.update_status(),
+                    Err(e) => main_window.status_label.set_text(&e)
+                }
+            }
+        });
+
+        self.offer_draw_button.connect_clicked({
+            let main_window = self.clone();
+            move |_button| {
+                main_window.offer_draw();
+            }
+        });
+
+        let save_pgn_button = Button::new_with_label("Save PGN");
+        save_pgn_button.connect_clicked({
+            let main_window = self.clone();
+            move |_button| {
+                main_window.save_pgn();
+            }
+        });
+
+        let export_diagram_button = Button::new_with_label("Export diagram");
+        export_diagram_button.connect_clicked({
+            let main_window = self.clone();
+            move |_button| {
+                main_window.export_diagram();
+            }
+        });
+
+        let preferences_button = Button::new_with_label("Preferences");
+        preferences_button.connect_clicked({
+            let main_window = self.clone();
+            move |_button| {
+                main_window.open_preferences();
+            }
+        });
+
+        [&self.resign_button, &self.claim_draw_button, &self.offer_draw_button,
+            &save_pgn_button, &export_diagram_button, &preferences_button]
+            .iter()
+            .for_each(|button| {
+                self.game_actions.pack_start(
+                    *button,
+                    true,
+                    true,
+                    0,
+                );
+            });
+    }
+
+    fn ask_new_game(&self)
+    {
+        let start_fen = self.chessboard.borrow().get_game().get_start_fen().to_string();
+        let choice = ask_new_game(&self.window, &start_fen, self.side_choice.get(), self.goal.get());
+        if let Some((fen, side_choice, goal)) = choice {
+            self.review_queue.borrow_mut().clear();
+            self.reviewed_position.set(None);
+            if let Err(e) = self.start_session(&fen, side_choice, goal) {
+                self.status_label.set_text(&e);
+            }
+        }
+    }
+
+    fn start_session(&self, fen: &str, side_choice: SideChoice, goal: TrainingGoal) -> Result<(), String>
+    {
+        self.chessboard.borrow().load_position(fen)?;
+        self.side_choice.set(side_choice);
+        self.goal.set(goal);
+
+        let white_to_move = self.chessboard.borrow().get_game().is_white_turn();
+        let user_side = side_choice.resolve(white_to_move);
+        self.user_side.set(user_side);
+        self.chessboard.borrow_mut().set_reversed(user_side == Player::Black);
+
+        self.apply_mode();
+        Ok(())
+    }
+
+    fn set_mode(&self, mode: AppMode)
+    {
+        self.mode.set(mode);
+        if mode != AppMode::Training {
+            if let Some(ref engine) = *self.engine.borrow() {
+                engine.cancel_searches(SearchPurpose::PlayMove);
+                engine.cancel_searches(SearchPurpose::AnswerDrawOffer);
+            }
+        }
+        self.apply_mode();
+    }
+
+    fn apply_mode(&self)
+    {
+        let training = self.mode.get() == AppMode::Training;
+        let human_side = if training { Some(self.user_side.get()) } else { None };
+        self.chessboard.borrow().set_human_side(human_side);
+
+        [&self.resign_button, &self.claim_draw_button, &self.offer_draw_button]
+            .iter()
+            .for_each(|action| action.set_sensitive(training));
+        if training {
+            self.analysis_panel.get_widget().hide();
+        }
+        else {
+            self.analysis_panel.get_widget().show_all();
+        }
+
+        self.update_status();
+        self.refresh_analysis();
+        self.let_computer_play_if_needed();
+    }
+
+    fn refresh_analysis(&self)
+    {
+        if self.mode.get() != AppMode::Analysis {
+            return;
+        }
+        self.analysis_panel.update_tree(&self.chessboard.borrow().get_game());
+
+        let (fen, game_over) = {
+            let chessboard = self.chessboard.borrow();
+            let game = chessboard.get_game();
+            (game.get_fen(), game.get_result().is_some())
+        };
+        if game_over {
+            self.analysis_panel.set_evaluation("");
+            return;
+        }
+        if let Err(e) = self.start_engine_if_needed() {
+            self.analysis_panel.set_evaluation(&e);
+            return;
+        }
+
+        let movetime_ms = self.preferences.borrow().engine_movetime_ms;
+        if let Some(ref engine) = *self.engine.borrow() {
+            engine.request_search(SearchPurpose::Analyse, &fen, movetime_ms);
+        }
+        self.analysis_panel.set_evaluation("The engine is thinking ...");
+    }
+
+    fn computer_side(&self) -> Option<Player>
+    {
+        self.chessboard.borrow().get_human_side().map(|side| side.other_player())
+    }
+
+    /// Any search for a previous position is cancelled, for instance after going back in the history.
+    fn let_computer_play_if_needed(&self)
+    {
+        if let Some(ref engine) = *self.engine.borrow() {
+            engine.cancel_searches(SearchPurpose::PlayMove);
+        }
+        let computer_to_move = match self.computer_side() {
+            Some(side) => self.chessboard.borrow().get_game().waits_for(side),
+            None => false
+        };
+        if ! computer_to_move {
+            return;
+        }
+        if let Err(e) = self.start_engine_if_needed() {
+            self.status_label.set_text(&e);
+            return;
+        }
+
+        let fen = self.chessboard.borrow().get_game().get_fen();
+        let movetime_ms = self.preferences.borrow().engine_movetime_ms;
+        if let Some(ref engine) = *self.engine.borrow() {
+            engine.request_search(SearchPurpose::PlayMove, &fen, movetime_ms);
+        }
+        self.status_label.set_text("The computer is thinking ...");
+    }
+
+    fn update_status(&self)
+    {
+        let material_balance = self.chessboard.borrow().get_game().material_balance();
+        self.material_label.set_text(&material_balance.description());
+
+        let result = self.chessboard.borrow().get_game().get_result();
+        match result {
+            Some(result) if self.mode.get() == AppMode::Training =>
+                self.status_label.set_text(&format!("Game over : {}", result.description())),
+            Some(result) => self.status_label.set_text(&result.description()),
+            None => self.status_label.set_text("")
+        }
+
+        self.update_goal_status();
+    }
+
+    /// The goal is judged again from the whole game, after each move as at the end of the game.
+    fn update_goal_status(&self)
+    {
+        if self.mode.get() != AppMode::Training {
+            self.goal_label.set_text("");
+            return;
+        }
+        let goal = self.goal.get();
+        let status = goal.evaluate(
+            &self.chessboard.borrow().get_game(),
+            self.user_side.get(),
+            &*self.tablebase.borrow()
+        );
+        let questions = self.tablebase.borrow_mut().take_questions();
+        let without_tablebases = self.preferences.borrow().syzygy_path.is_empty();
+        let text = match status {
+            GoalStatus::Pending if without_tablebases && matches!(goal, TrainingGoal::TablebaseWin(_)) => format!(
+                "Goal : {} (choose the Syzygy tablebases folder in the preferences to check it)",
+                goal.description()
+            ),
+            GoalStatus::Pending => format!("Goal : {}", goal.description()),
+            GoalStatus::Achieved => format!("Goal achieved : {} !", goal.description()),
+            GoalStatus::Failed => format!("Goal failed : {}.", goal.description()),
+        };
+        if ! without_tablebases {
+            self.probe_tablebase(&questions);
+        }
+        let text = match self.reviewed_position.get() {
+            Some(_) => format!("{}   (review : {} more positions)", text, self.review_queue.borrow().len()),
+            None => text
+        };
+        self.goal_label.set_text(&text);
+
+        if status != GoalStatus::Pending {
+            if let Some(index) = self.reviewed_position.take() {
+                // Out of the board callbacks, as the next position replaces the game.
+                gtk::idle_add({
+                    let main_window = self.clone();
+                    move || {
+                        main_window.finish_review(index, status);
+                        Continue(false)
+                    }
+                });
+            }
+        }
+    }
+
+    fn probe_tablebase(&self, fens: &[String])
+    {
+        if fens.is_empty() {
+            return;
+        }
+        if let Err(e) = self.start_engine_if_needed() {
+            self.status_label.set_text(&e);
+            return;
+        }
+        if let Some(ref engine) = *self.engine.borrow() {
+            fens.iter().for_each(|fen| {
+                engine.request_search(SearchPurpose::ProbeTablebase, fen, TABLEBASE_PROBE_MOVETIME_MS);
+            });
+        }
+    }
+
+    /// Adds the current position, with its side and goal, to the library, due at once.
+    fn add_to_library(&self)
+    {
+        let fen = self.chessboard.borrow().get_game().get_start_fen().to_string();
+        let added = self.library.borrow_mut().add(&fen, self.side_choice.get(), self.goal.get(), today());
+        let saved = self.library.borrow().save();
+        match saved {
+            Err(e) => self.status_label.set_text(&e),
+            Ok(()) if added => self.status_label.set_text("Position added to the library."),
+            Ok(()) => self.status_label.set_text("Position already in the library : side and goal updated."),
+        }
+    }
+
+    fn review_due_positions(&self)
+    {
+        let due_positions = self.library.borrow().due_positions(today());
+        if due_positions.is_empty() {
+            self.status_label.set_text("No position to review today.");
+            return;
+        }
+        self.review_queue.replace(due_positions);
+        self.mode_combo.set_active(0);
+        self.review_next_position();
+    }
+
+    fn review_next_position(&self)
+    {
+        loop {
+            let index = match self.review_queue.borrow_mut().first().cloned() {
+                Some(index) => index,
+                None => {
+                    self.status_label.set_text("Review finished : no position left for today.");
+                    return;
+                }
+            };
+            self.review_queue.borrow_mut().remove(0);
+            let position = match self.library.borrow().positions.get(index) {
+                Some(position) => position.clone(),
+                None => continue
+            };
+            self.reviewed_position.set(Some(index));
+            match self.start_session(&position.fen, position.side_choice, position.goal) {
+                Ok(()) => return,
+                Err(e) => {
+                    self.reviewed_position.set(None);
+                    self.status_label.set_text(&e);
+                }
+            }
+        }
+    }
+
+    /// Schedules the reviewed position from its result and the difficulty felt by the user,
+    /// once the user has seen the result.
+    /// Closing the dialog stops the review, the position staying due.
+    fn finish_review(&self, index: usize, status: GoalStatus)
+    {
+        let goal = self.goal.get().description();
+        let grade = if status == GoalStatus::Achieved {
+            let message = format!("Goal achieved : {} ! How hard was this position ?", goal);
+            ask_grade(&self.window, &message, &SUCCESS_GRADES)
+        }
+        else {
+            let message = format!("Goal failed : {}. The position will be reviewed again tomorrow.", goal);
+            ask_grade(&self.window, &message, &[Grade::Again])
+        };
+        let grade = match grade {
+            Some(grade) => grade,
+            None => {
+                self.review_queue.borrow_mut().clear();
+                self.status_label.set_text("Review stopped : the position was not graded.");
+                return;
+            }
+        };
+        self.library.borrow_mut().grade(index, grade, today());
+        let saved = self.library.borrow().save();
+        if let Err(e) = saved {
+            self.status_label.set_text(&e);
+        }
+        self.review_next_position();
+    }
+
+    fn start_engine_if_needed(&self) -> Result<(), String>
+    {
+        if self.engine.borrow().is_none() {
+            let engine_path = self.preferences.borrow().engine_path.clone();
+            let syzygy_path = self.preferences.borrow().syzygy_path.clone();
+            let engine = EngineOpponent::start(&engine_path, &syzygy_path)?;
+            self.engine.replace(Some(engine));
+        }
+        Ok(())
+    }
+
+    fn offer_draw(&self)
+    {
+        if self.chessboard.borrow().get_game().get_result().is_some() {
+            return;
+        }
+        if let Err(e) = self.start_engine_if_needed() {
+            self.status_label.set_text(&e);
+            return;
+        }
+
+        let fen = self.chessboard.borrow().get_game().get_fen();
+        let movetime_ms = self.preferences.borrow().engine_movetime_ms;
+        if let Some(ref engine) = *self.engine.borrow() {
+            engine.request_search(SearchPurpose::AnswerDrawOffer, &fen, movetime_ms);
+        }
+        self.status_label.set_text("Draw offered, the engine is thinking ...");
+    }
+
+    fn poll_engine(&self)
+    {
+        let reply = match *self.engine.borrow() {
+            Some(ref engine) => engine.try_reply(),
+            None => None
+        };
+        let reply = match reply {
+            Some(reply) => reply,
+            None => return
+        };
+
+        let (current_fen, white_to_move, moves_played) = {
+            let chessboard = self.chessboard.borrow();
+            let game = chessboard.get_game();
+            (game.get_fen(), game.is_white_turn(), game.get_history().len() as u32 / 2)
+        };
+
+        match reply {
+            // Kept whatever the current position, as the goal replays the whole game.
+            EngineReply::SearchDone { purpose: SearchPurpose::ProbeTablebase, fen, score, tablebase_hits, .. } => {
+                let answer = TablebaseAnswer::from_engine_search(score, tablebase_hits);
+                self.tablebase.borrow_mut().insert(&fen, answer);
+                self.update_goal_status();
+            },
+            EngineReply::SearchDone { fen, .. } if fen != current_fen => {},
+            EngineReply::SearchDone { purpose: SearchPurpose::PlayMove, best_move, .. } => {
+                if self.mode.get() != AppMode::Training {
+                    return;
+                }
+                self.status_label.set_text("");
+                let outcome = self.chessboard.borrow().play_engine_move(&best_move);
+                if let Err(e) = outcome {
+                    self.status_label.set_text(&format!("{}", e));
+                }
+            },
+            EngineReply::SearchDone { purpose: SearchPurpose::AnswerDrawOffer, score, .. } => {
+                if self.mode.get() != AppMode::Training {
+                    return;
+                }
+                // Without a computer side, the draw is offered by the side to move.
+                let engine_plays_white = match self.computer_side() {
+                    Some(side) => side == Player::White,
+                    None => ! white_to_move
+                };
+                let policy = self.preferences.borrow().draw_offer_policy;
+                let accepted = match score {
+                    Some(score) if engine_plays_white == white_to_move => policy.accepts(score, moves_played),
+                    Some(score) => policy.accepts(score.negated(), moves_played),
+                    None => false
+                };
+                if accepted {
+                    self.chessboard.borrow().agree_draw();
+                    self.update_status();
+                }
+                else {
+                    self.status_label.set_text("The engine declines the draw offer.");
+                }
+            },
+            EngineReply::SearchDone { purpose: SearchPurpose::Analyse, score, principal_variation, .. } => {
+                if self.mode.get() != AppMode::Analysis {
+                    return;
+                }
+                let score_text = match score {
+                    Some(score) => format_score(if white_to_move { score } else { score.negated() }),
+                    None => String::from("?")
+                };
+                let line = self.chessboard.borrow().get_game().uci_line_to_san(&principal_variation);
+                self.analysis_panel.set_evaluation(&format!("{}   {}", score_text, line.join(" ")));
+            },
+            EngineReply::Failure(e) => {
+                self.engine.replace(None);
+                // The questions left unanswered are asked again to the next engine.
+                self.tablebase.borrow_mut().clear();
+                self.status_label.set_text(&e);
+            },
+        }
+    }
+
+    fn save_pgn(&self)
+    {
+        let dialog = FileChooserDialog::new(
+            Some("Save the game as PGN"),
+            Some(&self.window),
+            FileChooserAction::Save,
+        );
+        dialog.add_button("Cancel", ResponseType::Cancel.to_glib());
+        dialog.add_button("Save", ResponseType::Accept.to_glib());
+        dialog.set_current_name("game.pgn");
+        dialog.set_do_overwrite_confirmation(true);
+
+        let response = dialog.run();
+        let file_path = dialog.get_filename();
+        dialog.destroy();
+
+        if response == ResponseType::Accept.to_glib() {
+            if let Some(file_path) = file_path {
+                let pgn = self.chessboard.borrow().get_game().to_pgn();
+                if let Err(e) = fs::write(&file_path, pgn) {
+                    self.status_label.set_text(&format!("Could not save {} ({})", file_path.display(), e));
+                }
+            }
+        }
+    }
+
+    fn export_diagram(&self)
+    {
+        let mut options = self.diagram_options.get();
+        options.reversed = self.chessboard.borrow().is_reversed();
+        options.coordinates.uppercase = self.preferences.borrow().coordinates.uppercase;
+        options.coordinates.cells_colours = self.preferences.borrow().coordinates.cells_colours;
+        let (options, format) = match ask_diagram_options(&self.window, &options, self.diagram_format.get()) {
+            Some(choice) => choice,
+            None => return
+        };
+        self.diagram_options.set(options);
+        self.diagram_format.set(format);
+
+        let dialog = FileChooserDialog::new(
+            Some("Export the diagram"),
+            Some(&self.window),
+            FileChooserAction::Save,
+        );
+        dialog.add_button("Cancel", ResponseType::Cancel.to_glib());
+        dialog.add_button("Save", ResponseType::Accept.to_glib());
+        dialog.set_current_name(format!("diagram.{}", format.extension()));
+        dialog.set_do_overwrite_confirmation(true);
+
+        let response = dialog.run();
+        let file_path = dialog.get_filename();
+        dialog.destroy();
+
+        if response == ResponseType::Accept.to_glib() {
+            if let Some(file_path) = file_path {
+                // A file name typed with another extension gives the format.
+                let format = DiagramFormat::from_path(&file_path).unwrap_or(format);
+                let theme = theme_by_name(&self.preferences.borrow().theme);
+                let piece_set = piece_set_by_name(&self.preferences.borrow().piece_set);
+                let exported = export_diagram(
+                    &self.chessboard.borrow().get_game(),
+                    &options,
+                    &theme,
+                    &piece_set,
+                    format,
+                    &file_path
+                );
+                if let Err(e) = exported {
+                    self.status_label.set_text(&e);
+                }
+            }
+        }
+    }
+
+    fn open_preferences(&self)
+    {
+        let current_preferences = self.preferences.borrow().clone();
+        if let Some(new_preferences) = edit_preferences(&self.window, &current_preferences) {
+            let tablebases_changed = new_preferences.syzygy_path != current_preferences.syzygy_path;
+            if new_preferences.engine_path != current_preferences.engine_path || tablebases_changed {
+                self.engine.replace(None);
+            }
+            if let Err(e) = new_preferences.save() {
+                self.status_label.set_text(&e);
+            }
+            self.preferences.replace(new_preferences);
+            self.apply_board_preferences();
+            if tablebases_changed {
+                self.tablebase.borrow_mut().clear();
+                self.update_goal_status();
+            }
+        }
+    }
+
+    fn apply_board_preferences(&self)
+    {
+        let theme = theme_by_name(&self.preferences.borrow().theme);
+        let piece_set = piece_set_by_name(&self.preferences.borrow().piece_set);
+        self.chessboard.borrow().set_theme(theme);
+        self.chessboard.borrow().set_piece_set(piece_set);
+        self.chessboard.borrow().set_auto_queen(self.preferences.borrow().auto_queen);
+        self.chessboard.borrow().set_coordinates(self.preferences.borrow().coordinates);
+        self.chessboard.borrow().set_blindfold(
+            self.preferences.borrow().blindfold,
+            self.preferences.borrow().fade_delay_seconds
+        );
+    }
+
+    fn set_size_and_title(&self){
         self.window.set_title("Chess Position Trainer");
         let window_width = 50i32 * 9;
-        self.window.set_default_size(window_width, window_width + 65);
+        self.window.set_default_size(window_width, window_width + 195);
     }
 
-    fn set_icon(&mut self){
+    fn set_icon(&self){
         let icon_stream = MemoryInputStream::new_from_bytes(
             &Bytes::from_static(include_bytes!("../../resources/Chess_ql.png"))
         );
@@ -104,4 +977,13 @@ impl MainWindow
         };
         self.window.set_icon(&icon);
     }
+}
+
+/// Formats a score given from the white point of view, such as +0.35 or #-3.
+fn format_score(score: EngineScore) -> String
+{
+    match score {
+        EngineScore::Centipawns(value) => format!("{:+.2}", value as f64 / 100.0),
+        EngineScore::MateIn(moves) => format!("#{}", moves),
+    }
 }
\ No newline at end of file
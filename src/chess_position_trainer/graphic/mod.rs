@@ -5,4 +5,17 @@ pub mod chessboard;
 pub use self::chessboard::ChessBoard;
 
 pub mod load_images;
-pub use self::load_images::load_image;
\ No newline at end of file
+pub use self::load_images::load_image;
+
+pub mod preferences_dialog;
+pub mod new_game_dialog;
+pub mod analysis_panel;
+pub mod theme;
+pub mod piece_set;
+pub mod animation;
+pub mod promotion_picker;
+pub mod geometry;
+pub mod board_painter;
+pub mod diagram;
+pub mod export_diagram_dialog;
+pub mod review_grade_dialog;
\ No newline at end of file
@@ -0,0 +1,86 @@
+use gtk::prelude::*;
+use gtk::{Dialog, Window, Grid, Label, Entry, ComboBoxText, SpinButton, ResponseType};
+use glib::translate::ToGlib;
+use super::super::logic::side_choice::{SideChoice, ALL_SIDE_CHOICES};
+use super::super::logic::training_goal::{TrainingGoal, ALL_TRAINING_GOALS};
+
+/// Asks for the position to train on, the side played by the user and the goal to reach.
+/// Gives None if the user cancelled.
+pub fn ask_new_game(parent: &Window, fen: &str, side_choice: SideChoice, goal: TrainingGoal)
+    -> Option<(String, SideChoice, TrainingGoal)>
+{
+    let dialog = Dialog::new();
+    dialog.set_title("New game");
+    dialog.set_modal(true);
+    dialog.set_transient_for(parent);
+    dialog.add_button("Cancel", ResponseType::Cancel.to_glib());
+    dialog.add_button("Start", ResponseType::Ok.to_glib());
+
+    let grid = Grid::new();
+    grid.set_row_spacing(10);
+    grid.set_column_spacing(10);
+
+    let fen_entry = Entry::new();
+    fen_entry.set_text(fen);
+    fen_entry.set_width_chars(60);
+
+    let side_combo = ComboBoxText::new();
+    ALL_SIDE_CHOICES.iter().enumerate().for_each(|(index, choice)| {
+        side_combo.append_text(choice.label());
+        if *choice == side_choice {
+            side_combo.set_active(index as i32);
+        }
+    });
+
+    let goal_combo = ComboBoxText::new();
+    ALL_TRAINING_GOALS.iter().enumerate().for_each(|(index, training_goal)| {
+        goal_combo.append_text(training_goal.kind_label());
+        if training_goal.kind_label() == goal.kind_label() {
+            goal_combo.set_active(index as i32);
+        }
+    });
+    let count_button = SpinButton::new_with_range(1.0, 100.0, 1.0);
+    count_button.set_value(goal.count().unwrap_or(3) as f64);
+    count_button.set_sensitive(goal.count().is_some());
+    goal_combo.connect_changed({
+        let count_button = count_button.clone();
+        move |combo| {
+            if let Some(training_goal) = ALL_TRAINING_GOALS.get(combo.get_active() as usize) {
+                count_button.set_sensitive(training_goal.count().is_some());
+            }
+        }
+    });
+
+    grid.attach(&Label::new("Position (FEN)"), 0, 0, 1, 1);
+    grid.attach(&fen_entry, 1, 0, 1, 1);
+    grid.attach(&Label::new("You play"), 0, 1, 1, 1);
+    grid.attach(&side_combo, 1, 1, 1, 1);
+    grid.attach(&Label::new("Goal"), 0, 2, 1, 1);
+    grid.attach(&goal_combo, 1, 2, 1, 1);
+    grid.attach(&Label::new("N"), 0, 3, 1, 1);
+    grid.attach(&count_button, 1, 3, 1, 1);
+
+    dialog.get_content_area().pack_start(
+        &grid,
+        true,
+        true,
+        10
+    );
+    dialog.show_all();
+
+    let response = dialog.run();
+    let new_game = if response == ResponseType::Ok.to_glib() {
+        let fen = fen_entry.get_text().unwrap_or_default();
+        let side_choice = ALL_SIDE_CHOICES.get(side_combo.get_active() as usize)
+            .cloned()
+            .unwrap_or(side_choice);
+        let goal = ALL_TRAINING_GOALS.get(goal_combo.get_active() as usize)
+            .map(|training_goal| training_goal.with_count(count_button.get_value_as_int() as u32))
+            .unwrap_or(goal);
+        Some((fen.trim().to_string(), side_choice, goal))
+    }
+    else { None };
+
+    dialog.destroy();
+    new_game
+}
\ No newline at end of file
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use gdk_pixbuf::Pixbuf;
+use super::load_image;
+use super::super::logic::preferences::application_folder;
+pub use super::super::logic::preferences::DEFAULT_PIECE_SET;
+
+const PIECE_SETS_FOLDER: &str = "pieces";
+const PIECES_CHARS: [char; 12] = ['P', 'N', 'B', 'R', 'Q', 'K', 'p', 'n', 'b', 'r', 'q', 'k'];
+
+/// A set of pieces images : either the images shipped with the application,
+/// or a folder holding one PNG or SVG file per piece.
+/// The files are named after the piece letter with its colour, such as wK.svg or bp.png,
+/// or after the FEN letter alone, such as K.svg or p.png.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PieceSet
+{
+    pub name: String,
+    folder: Option<PathBuf>,
+}
+
+impl PieceSet
+{
+    pub fn default_set() -> PieceSet
+    {
+        PieceSet {
+            name: String::from(DEFAULT_PIECE_SET),
+            folder: None,
+        }
+    }
+
+    /// The images of the twelve pieces at the given size, indexed by their FEN letter.
+    /// A piece missing from the set is taken from the default set.
+    pub fn load_images(&self, size: u32) -> HashMap<char, Pixbuf>
+    {
+        let mut images = HashMap::new();
+        PIECES_CHARS.iter().for_each(|piece_char| {
+            let image = self.folder.as_ref()
+                .and_then(|folder| load_image_file(folder, *piece_char, size))
+                .or_else(|| load_image(default_image_bytes(*piece_char), size as i32));
+            match image {
+                Some(image) => { images.insert(*piece_char, image); },
+                None => println!("Failed to get the image of piece {} !", piece_char)
+            }
+        });
+        images
+    }
+}
+
+/// The default set, followed by the sets found in the pieces folder
+/// of the user configuration folder, sorted by name.
+pub fn available_piece_sets() -> Vec<PieceSet>
+{
+    let mut folder_sets: Vec<PieceSet> = application_folder()
+        .map(|folder| folder.join(PIECE_SETS_FOLDER))
+        .and_then(|folder| fs::read_dir(folder).ok())
+        .map(|entries| entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter_map(|path| {
+                let name = path.file_name()?.to_string_lossy().to_string();
+                Some(PieceSet { name, folder: Some(path) })
+            })
+            .collect())
+        .unwrap_or_default();
+    folder_sets.sort_by(|first, second| first.name.cmp(&second.name));
+
+    let mut sets = vec![PieceSet::default_set()];
+    sets.extend(folder_sets);
+    sets
+}
+
+/// The piece set with the given name, or the default one if there is none.
+pub fn piece_set_by_name(name: &str) -> PieceSet
+{
+    available_piece_sets().into_iter()
+        .find(|set| set.name == name)
+        .unwrap_or_else(PieceSet::default_set)
+}
+
+fn load_image_file(folder: &Path, piece_char: char, size: u32) -> Option<Pixbuf>
+{
+    let colour = if piece_char.is_uppercase() { 'w' } else { 'b' };
+    let names = [
+        format!("{}{}", colour, piece_char.to_ascii_uppercase()),
+        format!("{}{}", colour, piece_char.to_ascii_lowercase()),
+        piece_char.to_string(),
+    ];
+    names.iter()
+        .flat_map(|name| vec![folder.join(format!("{}.svg", name)), folder.join(format!("{}.png", name))])
+        .filter(|path| path.is_file())
+        .filter_map(|path| match Pixbuf::new_from_file_at_scale(&path, size as i32, size as i32, true) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                println!("Failed to load {} ! ({})", path.display(), e);
+                None
+            }
+        })
+        .next()
+}
+
+fn default_image_bytes(piece_char: char) -> &'static [u8]
+{
+    match piece_char {
+        'P' => include_bytes!("../../resources/Chess_pl.png"),
+        'N' => include_bytes!("../../resources/Chess_nl.png"),
+        'B' => include_bytes!("../../resources/Chess_bl.png"),
+        'R' => include_bytes!("../../resources/Chess_rl.png"),
+        'Q' => include_bytes!("../../resources/Chess_ql.png"),
+        'K' => include_bytes!("../../resources/Chess_kl.png"),
+        'p' => include_bytes!("../../resources/Chess_pd.png"),
+        'n' => include_bytes!("../../resources/Chess_nd.png"),
+        'b' => include_bytes!("../../resources/Chess_bd.png"),
+        'r' => include_bytes!("../../resources/Chess_rd.png"),
+        'q' => include_bytes!("../../resources/Chess_qd.png"),
+        _ => include_bytes!("../../resources/Chess_kd.png"),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,184 @@
+use gtk::prelude::*;
+use gtk::{Dialog, Window, Grid, Label, Entry, SpinButton, ComboBoxText, Image, ResponseType,
+    Box as GtkBox, Orientation, CheckButton};
+use glib::translate::ToGlib;
+use super::super::logic::preferences::Preferences;
+use super::super::logic::blindfold::ALL_BLINDFOLD_MODES;
+use super::super::logic::coordinates::ALL_COORDINATES_PLACEMENTS;
+use super::theme::builtin_themes;
+use super::piece_set::{PieceSet, available_piece_sets};
+
+const PREVIEW_PIECES: [char; 12] = ['K', 'Q', 'R', 'B', 'N', 'P', 'k', 'q', 'r', 'b', 'n', 'p'];
+const PREVIEW_SIZE: u32 = 32;
+
+/// Opens a modal dialog editing the given preferences.
+/// Gives the new preferences, or None if the user cancelled.
+pub fn edit_preferences(parent: &Window, preferences: &Preferences) -> Option<Preferences>
+{
+    let dialog = Dialog::new();
+    dialog.set_title("Preferences");
+    dialog.set_modal(true);
+    dialog.set_transient_for(parent);
+    dialog.add_button("Cancel", ResponseType::Cancel.to_glib());
+    dialog.add_button("OK", ResponseType::Ok.to_glib());
+
+    let grid = Grid::new();
+    grid.set_row_spacing(10);
+    grid.set_column_spacing(10);
+
+    let engine_path_entry = Entry::new();
+    engine_path_entry.set_text(&preferences.engine_path);
+
+    let movetime_button = SpinButton::new_with_range(100.0, 60000.0, 100.0);
+    movetime_button.set_value(preferences.engine_movetime_ms as f64);
+
+    let draw_advantage_button = SpinButton::new_with_range(-1000.0, 1000.0, 10.0);
+    draw_advantage_button.set_value(preferences.draw_offer_policy.max_engine_advantage_cp as f64);
+
+    let draw_moves_button = SpinButton::new_with_range(0.0, 500.0, 1.0);
+    draw_moves_button.set_value(preferences.draw_offer_policy.min_moves_played as f64);
+
+    let syzygy_path_entry = Entry::new();
+    syzygy_path_entry.set_placeholder_text("Needed by the tablebase win goal");
+    syzygy_path_entry.set_text(&preferences.syzygy_path);
+
+    let themes = builtin_themes();
+    let theme_combo = ComboBoxText::new();
+    themes.iter().enumerate().for_each(|(index, theme)| {
+        theme_combo.append_text(theme.name);
+        if theme.name == preferences.theme {
+            theme_combo.set_active(index as i32);
+        }
+    });
+
+    let piece_sets = available_piece_sets();
+    let piece_set_combo = ComboBoxText::new();
+    piece_sets.iter().enumerate().for_each(|(index, piece_set)| {
+        piece_set_combo.append_text(&piece_set.name);
+        if piece_set.name == preferences.piece_set {
+            piece_set_combo.set_active(index as i32);
+        }
+    });
+    if piece_set_combo.get_active() < 0 {
+        piece_set_combo.set_active(0);
+    }
+
+    let piece_set_preview = GtkBox::new(Orientation::Horizontal, 2);
+    let preview_images: Vec<Image> = PREVIEW_PIECES.iter().map(|_piece| {
+        let image = Image::new();
+        piece_set_preview.pack_start(&image, false, false, 0);
+        image
+    }).collect();
+    if let Some(piece_set) = piece_sets.get(piece_set_combo.get_active() as usize) {
+        show_piece_set_preview(piece_set, &preview_images);
+    }
+    piece_set_combo.connect_changed({
+        let piece_sets = piece_sets.clone();
+        move |combo| {
+            if let Some(piece_set) = piece_sets.get(combo.get_active() as usize) {
+                show_piece_set_preview(piece_set, &preview_images);
+            }
+        }
+    });
+
+    let auto_queen_button = CheckButton::new_with_label("Always promote to a queen");
+    auto_queen_button.set_active(preferences.auto_queen);
+
+    let blindfold_combo = ComboBoxText::new();
+    ALL_BLINDFOLD_MODES.iter().enumerate().for_each(|(index, mode)| {
+        blindfold_combo.append_text(mode.label());
+        if *mode == preferences.blindfold {
+            blindfold_combo.set_active(index as i32);
+        }
+    });
+
+    let fade_delay_button = SpinButton::new_with_range(0.0, 600.0, 1.0);
+    fade_delay_button.set_value(preferences.fade_delay_seconds as f64);
+
+    let coordinates_combo = ComboBoxText::new();
+    ALL_COORDINATES_PLACEMENTS.iter().enumerate().for_each(|(index, placement)| {
+        coordinates_combo.append_text(placement.label());
+        if *placement == preferences.coordinates.placement {
+            coordinates_combo.set_active(index as i32);
+        }
+    });
+    let uppercase_button = CheckButton::new_with_label("Uppercase files letters");
+    uppercase_button.set_active(preferences.coordinates.uppercase);
+    let cells_colours_button = CheckButton::new_with_label("Coordinates in the cells colours");
+    cells_colours_button.set_active(preferences.coordinates.cells_colours);
+
+    grid.attach(&Label::new("Engine executable"), 0, 0, 1, 1);
+    grid.attach(&engine_path_entry, 1, 0, 1, 1);
+    grid.attach(&Label::new("Engine thinking time (ms)"), 0, 1, 1, 1);
+    grid.attach(&movetime_button, 1, 1, 1, 1);
+    grid.attach(&Label::new("Draw offers : max engine advantage (centipawns)"), 0, 2, 1, 1);
+    grid.attach(&draw_advantage_button, 1, 2, 1, 1);
+    grid.attach(&Label::new("Draw offers : min moves played"), 0, 3, 1, 1);
+    grid.attach(&draw_moves_button, 1, 3, 1, 1);
+    grid.attach(&Label::new("Board theme"), 0, 4, 1, 1);
+    grid.attach(&theme_combo, 1, 4, 1, 1);
+    grid.attach(&Label::new("Pieces"), 0, 5, 1, 1);
+    grid.attach(&piece_set_combo, 1, 5, 1, 1);
+    grid.attach(&piece_set_preview, 1, 6, 1, 1);
+    grid.attach(&auto_queen_button, 1, 7, 1, 1);
+    grid.attach(&Label::new("Blindfold training"), 0, 8, 1, 1);
+    grid.attach(&blindfold_combo, 1, 8, 1, 1);
+    grid.attach(&Label::new("Pieces fading out after (seconds)"), 0, 9, 1, 1);
+    grid.attach(&fade_delay_button, 1, 9, 1, 1);
+    grid.attach(&Label::new("Coordinates"), 0, 10, 1, 1);
+    grid.attach(&coordinates_combo, 1, 10, 1, 1);
+    grid.attach(&uppercase_button, 1, 11, 1, 1);
+    grid.attach(&cells_colours_button, 1, 12, 1, 1);
+    grid.attach(&Label::new("Syzygy tablebases folder"), 0, 13, 1, 1);
+    grid.attach(&syzygy_path_entry, 1, 13, 1, 1);
+
+    dialog.get_content_area().pack_start(
+        &grid,
+        true,
+        true,
+        10
+    );
+    dialog.show_all();
+
+    let response = dialog.run();
+    let new_preferences = if response == ResponseType::Ok.to_glib() {
+        let mut new_preferences = preferences.clone();
+        new_preferences.engine_path = engine_path_entry.get_text().unwrap_or_default();
+        new_preferences.engine_movetime_ms = movetime_button.get_value_as_int() as u32;
+        new_preferences.draw_offer_policy.max_engine_advantage_cp = draw_advantage_button.get_value_as_int();
+        new_preferences.draw_offer_policy.min_moves_played = draw_moves_button.get_value_as_int() as u32;
+        new_preferences.syzygy_path = syzygy_path_entry.get_text().unwrap_or_default();
+        if let Some(theme) = themes.get(theme_combo.get_active() as usize) {
+            new_preferences.theme = theme.name.to_string();
+        }
+        if let Some(piece_set) = piece_sets.get(piece_set_combo.get_active() as usize) {
+            new_preferences.piece_set = piece_set.name.clone();
+        }
+        new_preferences.auto_queen = auto_queen_button.get_active();
+        if let Some(mode) = ALL_BLINDFOLD_MODES.get(blindfold_combo.get_active() as usize) {
+            new_preferences.blindfold = *mode;
+        }
+        new_preferences.fade_delay_seconds = fade_delay_button.get_value_as_int() as u32;
+        if let Some(placement) = ALL_COORDINATES_PLACEMENTS.get(coordinates_combo.get_active() as usize) {
+            new_preferences.coordinates.placement = *placement;
+        }
+        new_preferences.coordinates.uppercase = uppercase_button.get_active();
+        new_preferences.coordinates.cells_colours = cells_colours_button.get_active();
+        Some(new_preferences)
+    }
+    else { None };
+
+    dialog.destroy();
+    new_preferences
+}
+
+fn show_piece_set_preview(piece_set: &PieceSet, preview_images: &[Image])
+{
+    let images = piece_set.load_images(PREVIEW_SIZE);
+    PREVIEW_PIECES.iter().zip(preview_images.iter()).for_each(|(piece_char, preview_image)| {
+        match images.get(piece_char) {
+            Some(image) => preview_image.set_from_pixbuf(image),
+            None => preview_image.clear()
+        }
+    });
+}
\ No newline at end of file
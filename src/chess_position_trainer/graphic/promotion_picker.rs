@@ -0,0 +1,101 @@
+use pleco::core::{PieceType};
+use pleco::core::sq::{SQ};
+
+const CHOICES: [PieceType; 4] = [PieceType::Q, PieceType::R, PieceType::B, PieceType::N];
+
+/// The promotion choices shown on the board, in the column of the promotion cell,
+/// from the promotion cell towards the center of the board.
+/// Being given in cells, they follow the board orientation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PromotionPicker
+{
+    pub start_cell: SQ,
+    pub end_cell: SQ,
+}
+
+impl PromotionPicker
+{
+    pub fn new(start_cell: SQ, end_cell: SQ) -> PromotionPicker
+    {
+        PromotionPicker {
+            start_cell,
+            end_cell,
+        }
+    }
+
+    /// Whether the promoted piece is white, which is the case when promoting on the 8th rank.
+    pub fn is_white(&self) -> bool
+    {
+        self.end_cell.rank_idx_of_sq() == 7
+    }
+
+    pub fn choice_cells(&self) -> Vec<(SQ, PieceType)>
+    {
+        let file = self.end_cell.file_idx_of_sq();
+        let rank = self.end_cell.rank_idx_of_sq();
+        CHOICES.iter().enumerate().map(|(index, piece_type)| {
+            let choice_rank = if self.is_white() { rank - index as u8 } else { rank + index as u8 };
+            (SQ::from(file + 8 * choice_rank), *piece_type)
+        }).collect()
+    }
+
+    /// The piece chosen by clicking on the given cell, or None if the click,
+    /// being outside of the choices, cancels the promotion.
+    pub fn choice_at(&self, cell: Option<SQ>) -> Option<PieceType>
+    {
+        let cell = cell?;
+        self.choice_cells().iter()
+            .find(|(choice_cell, _piece_type)| *choice_cell == cell)
+            .map(|(_choice_cell, piece_type)| *piece_type)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::super::super::logic::test_helpers::cell;
+
+    #[test]
+    fn white_choices_go_down_from_the_promotion_cell()
+    {
+        let picker = PromotionPicker::new(cell("e7"), cell("e8"));
+        assert!(picker.is_white());
+        assert_eq!(picker.choice_cells(), vec![
+            (cell("e8"), PieceType::Q),
+            (cell("e7"), PieceType::R),
+            (cell("e6"), PieceType::B),
+            (cell("e5"), PieceType::N),
+        ]);
+    }
+
+    #[test]
+    fn black_choices_go_up_from_the_promotion_cell()
+    {
+        let picker = PromotionPicker::new(cell("b2"), cell("a1"));
+        assert!(! picker.is_white());
+        assert_eq!(picker.choice_cells(), vec![
+            (cell("a1"), PieceType::Q),
+            (cell("a2"), PieceType::R),
+            (cell("a3"), PieceType::B),
+            (cell("a4"), PieceType::N),
+        ]);
+    }
+
+    #[test]
+    fn clicking_a_choice_gives_its_piece()
+    {
+        let picker = PromotionPicker::new(cell("g7"), cell("h8"));
+        assert_eq!(picker.choice_at(Some(cell("h8"))), Some(PieceType::Q));
+        assert_eq!(picker.choice_at(Some(cell("h5"))), Some(PieceType::N));
+    }
+
+    #[test]
+    fn clicking_outside_of_the_choices_cancels()
+    {
+        let picker = PromotionPicker::new(cell("g7"), cell("h8"));
+        assert_eq!(picker.choice_at(Some(cell("h4"))), None);
+        assert_eq!(picker.choice_at(Some(cell("g8"))), None);
+        assert_eq!(picker.choice_at(None), None);
+    }
+}
\ No newline at end of file
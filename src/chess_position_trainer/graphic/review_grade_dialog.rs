@@ -0,0 +1,31 @@
+use gtk::prelude::*;
+use gtk::{Dialog, Window, Label};
+use super::super::logic::spaced_repetition::Grade;
+
+/// Shows the result of the reviewed position and asks for one of the given grades.
+/// Gives None if the user closed the dialog.
+pub fn ask_grade(parent: &Window, message: &str, grades: &[Grade]) -> Option<Grade>
+{
+    let dialog = Dialog::new();
+    dialog.set_title("Review");
+    dialog.set_modal(true);
+    dialog.set_transient_for(parent);
+    grades.iter().enumerate().for_each(|(index, grade)| {
+        dialog.add_button(grade.label(), index as i32);
+    });
+
+    dialog.get_content_area().pack_start(
+        &Label::new(message),
+        true,
+        true,
+        10
+    );
+    dialog.show_all();
+
+    let response = dialog.run();
+    dialog.destroy();
+    if response >= 0 {
+        grades.get(response as usize).cloned()
+    }
+    else { None }
+}
\ No newline at end of file
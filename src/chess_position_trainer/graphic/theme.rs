@@ -0,0 +1,127 @@
+use super::super::logic::annotation::AnnotationColor;
+pub use super::super::logic::preferences::DEFAULT_THEME;
+
+/// Red, green and blue components, between 0.0 and 1.0.
+pub type Rgb = [f64; 3];
+/// Red, green, blue and opacity components, between 0.0 and 1.0.
+pub type Rgba = [f64; 4];
+
+/// The colours used for painting the board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme
+{
+    pub name: &'static str,
+    pub background: Rgb,
+    pub light_cells: Rgb,
+    pub dark_cells: Rgb,
+    pub coordinates: Rgb,
+    pub last_move: Rgba,
+    pub selected_cell: Rgba,
+    pub premoves: Rgba,
+    pub check: Rgb,
+    pub legal_destinations: Rgba,
+    /// Arrows and circles colours, in the order green, red, yellow and blue.
+    pub annotations: [Rgba; 4],
+}
+
+impl Theme
+{
+    pub fn annotation_color(&self, color: AnnotationColor) -> Rgba
+    {
+        match color {
+            AnnotationColor::Green => self.annotations[0],
+            AnnotationColor::Red => self.annotations[1],
+            AnnotationColor::Yellow => self.annotations[2],
+            AnnotationColor::Blue => self.annotations[3],
+        }
+    }
+}
+
+pub fn builtin_themes() -> Vec<Theme>
+{
+    vec![
+        Theme {
+            name: DEFAULT_THEME,
+            background: [60.0/255.0, 204.0/255.0, 100.0/255.0],
+            light_cells: [1.0, 1.0, 179.0/255.0],
+            dark_cells: [153.0/255.0, 102.0/255.0, 51.0/255.0],
+            coordinates: [0.2, 0.4, 1.0],
+            last_move: [1.0, 0.85, 0.0, 0.45],
+            selected_cell: [0.2, 0.6, 1.0, 0.5],
+            premoves: [0.9, 0.3, 0.9, 0.45],
+            check: [1.0, 0.0, 0.0],
+            legal_destinations: [0.1, 0.3, 0.1, 0.5],
+            annotations: [
+                [0.08, 0.47, 0.11, 0.8],
+                [0.53, 0.13, 0.13, 0.8],
+                [0.9, 0.68, 0.0, 0.8],
+                [0.0, 0.19, 0.53, 0.8],
+            ],
+        },
+        Theme {
+            name: "Ocean",
+            background: [0.16, 0.2, 0.27],
+            light_cells: [0.87, 0.89, 0.9],
+            dark_cells: [0.55, 0.64, 0.68],
+            coordinates: [0.95, 0.95, 0.95],
+            last_move: [0.6, 0.8, 0.2, 0.5],
+            selected_cell: [0.08, 0.33, 0.6, 0.5],
+            premoves: [0.6, 0.2, 0.6, 0.45],
+            check: [1.0, 0.0, 0.0],
+            legal_destinations: [0.08, 0.2, 0.33, 0.5],
+            annotations: [
+                [0.08, 0.47, 0.11, 0.8],
+                [0.53, 0.13, 0.13, 0.8],
+                [0.9, 0.68, 0.0, 0.8],
+                [0.0, 0.19, 0.53, 0.8],
+            ],
+        },
+        Theme {
+            name: "High contrast",
+            background: [0.0, 0.0, 0.0],
+            light_cells: [1.0, 1.0, 1.0],
+            dark_cells: [0.45, 0.45, 0.45],
+            coordinates: [1.0, 1.0, 0.0],
+            last_move: [1.0, 1.0, 0.0, 0.6],
+            selected_cell: [0.0, 1.0, 1.0, 0.6],
+            premoves: [1.0, 0.0, 1.0, 0.6],
+            check: [1.0, 0.0, 0.0],
+            legal_destinations: [0.0, 0.0, 0.0, 0.8],
+            annotations: [
+                [0.0, 0.8, 0.0, 0.9],
+                [1.0, 0.0, 0.0, 0.9],
+                [1.0, 0.85, 0.0, 0.9],
+                [0.0, 0.4, 1.0, 0.9],
+            ],
+        },
+        // Okabe-Ito palette, which stays distinguishable with the common colour vision deficiencies.
+        Theme {
+            name: "Colour-blind safe",
+            background: [0.3, 0.3, 0.3],
+            light_cells: [0.94, 0.89, 0.78],
+            dark_cells: [0.47, 0.55, 0.65],
+            coordinates: [0.94, 0.89, 0.26],
+            last_move: [0.34, 0.71, 0.91, 0.55],
+            selected_cell: [0.8, 0.47, 0.65, 0.55],
+            premoves: [0.0, 0.45, 0.7, 0.5],
+            check: [0.84, 0.37, 0.0],
+            legal_destinations: [0.0, 0.0, 0.0, 0.5],
+            annotations: [
+                [0.0, 0.62, 0.45, 0.85],
+                [0.84, 0.37, 0.0, 0.85],
+                [0.94, 0.89, 0.26, 0.85],
+                [0.0, 0.45, 0.7, 0.85],
+            ],
+        },
+    ]
+}
+
+/// The built-in theme with the given name, or the default one if there is none.
+pub fn theme_by_name(name: &str) -> Theme
+{
+    let themes = builtin_themes();
+    let default_theme = themes[0].clone();
+    themes.into_iter()
+        .find(|theme| theme.name == name)
+        .unwrap_or(default_theme)
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+use pleco::core::sq::{SQ};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnnotationColor
+{
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl AnnotationColor
+{
+    /// The letter used for the colour in the [%cal] and [%csl] PGN commands.
+    pub fn pgn_letter(&self) -> char
+    {
+        match self {
+            AnnotationColor::Green => 'G',
+            AnnotationColor::Red => 'R',
+            AnnotationColor::Yellow => 'Y',
+            AnnotationColor::Blue => 'B',
+        }
+    }
+}
+
+/// An arrow or a circle drawn by the user on a position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Annotation
+{
+    Arrow(SQ, SQ, AnnotationColor),
+    Circle(SQ, AnnotationColor),
+}
+
+impl Annotation
+{
+    pub fn get_color(&self) -> AnnotationColor
+    {
+        match self {
+            Annotation::Arrow(_, _, color) => *color,
+            Annotation::Circle(_, color) => *color,
+        }
+    }
+
+    /// Whether both annotations are drawn on the same cells, whatever their colours.
+    pub fn same_cells(&self, other: &Annotation) -> bool
+    {
+        match (self, other) {
+            (Annotation::Arrow(start, end, _), Annotation::Arrow(other_start, other_end, _)) =>
+                start == other_start && end == other_end,
+            (Annotation::Circle(cell, _), Annotation::Circle(other_cell, _)) => cell == other_cell,
+            _ => false
+        }
+    }
+}
+
+/// Adds the annotation, or removes it if it was already there with the same colour.
+/// An annotation on the same cells with another colour is replaced.
+pub fn toggle_annotation(annotations: &mut Vec<Annotation>, annotation: Annotation)
+{
+    let already_there = annotations.contains(&annotation);
+    annotations.retain(|existing| ! existing.same_cells(&annotation));
+    if ! already_there {
+        annotations.push(annotation);
+    }
+}
+
+/// The PGN comment content holding the annotations, such as "[%csl Gd4][%cal Re2e4,Gg1f3]",
+/// or an empty string without annotations.
+pub fn annotations_to_pgn(annotations: &[Annotation]) -> String
+{
+    let circles: Vec<String> = annotations.iter().filter_map(|annotation| match annotation {
+        Annotation::Circle(cell, color) => Some(format!("{}{}", color.pgn_letter(), cell)),
+        _ => None
+    }).collect();
+    let arrows: Vec<String> = annotations.iter().filter_map(|annotation| match annotation {
+        Annotation::Arrow(start, end, color) =>
+            Some(format!("{}{}{}", color.pgn_letter(), start, end)),
+        _ => None
+    }).collect();
+
+    let mut commands = String::new();
+    if ! circles.is_empty() {
+        commands.push_str(&format!("[%csl {}]", circles.join(",")));
+    }
+    if ! arrows.is_empty() {
+        commands.push_str(&format!("[%cal {}]", arrows.join(",")));
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn toggling()
+    {
+        let mut annotations = vec![];
+        toggle_annotation(&mut annotations, Annotation::Circle(SQ::D4, AnnotationColor::Green));
+        toggle_annotation(&mut annotations, Annotation::Arrow(SQ::E2, SQ::E4, AnnotationColor::Red));
+        assert_eq!(annotations, vec![
+            Annotation::Circle(SQ::D4, AnnotationColor::Green),
+            Annotation::Arrow(SQ::E2, SQ::E4, AnnotationColor::Red),
+        ]);
+
+        toggle_annotation(&mut annotations, Annotation::Circle(SQ::D4, AnnotationColor::Green));
+        assert_eq!(annotations, vec![Annotation::Arrow(SQ::E2, SQ::E4, AnnotationColor::Red)]);
+
+        toggle_annotation(&mut annotations, Annotation::Arrow(SQ::E2, SQ::E4, AnnotationColor::Blue));
+        assert_eq!(annotations, vec![Annotation::Arrow(SQ::E2, SQ::E4, AnnotationColor::Blue)]);
+
+        toggle_annotation(&mut annotations, Annotation::Arrow(SQ::E4, SQ::E2, AnnotationColor::Blue));
+        assert_eq!(annotations.len(), 2);
+    }
+
+    #[test]
+    fn pgn_commands()
+    {
+        assert_eq!(annotations_to_pgn(&[]), "");
+        assert_eq!(annotations_to_pgn(&[Annotation::Circle(SQ::D4, AnnotationColor::Yellow)]), "[%csl Yd4]");
+        assert_eq!(annotations_to_pgn(&[
+            Annotation::Arrow(SQ::E2, SQ::E4, AnnotationColor::Red),
+            Annotation::Circle(SQ::D4, AnnotationColor::Green),
+            Annotation::Arrow(SQ::G1, SQ::F3, AnnotationColor::Green),
+            Annotation::Circle(SQ::E5, AnnotationColor::Blue),
+        ]), "[%csl Gd4,Be5][%cal Re2e4,Gg1f3]");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+/// How the pieces are shown, so that the user can train visualising the position.
+/// The moves are still played the usual way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Blindfold
+{
+    Off,
+    HideAll,
+    Discs,
+    HideOpponent,
+    /// The pieces fade out some seconds after each position is shown.
+    Fade,
+}
+
+pub const ALL_BLINDFOLD_MODES: [Blindfold; 5] = [
+    Blindfold::Off,
+    Blindfold::HideAll,
+    Blindfold::Discs,
+    Blindfold::HideOpponent,
+    Blindfold::Fade,
+];
+
+/// How long the pieces take to disappear, once the delay is over.
+pub const FADE_DURATION: Duration = Duration::from_millis(1000);
+
+impl Blindfold
+{
+    pub fn label(&self) -> &'static str
+    {
+        match self {
+            Blindfold::Off => "All pieces shown",
+            Blindfold::HideAll => "All pieces hidden",
+            Blindfold::Discs => "Discs without the piece type",
+            Blindfold::HideOpponent => "Computer pieces hidden",
+            Blindfold::Fade => "Pieces fading out",
+        }
+    }
+
+    /// The name saved in the preferences.
+    pub fn key(&self) -> &'static str
+    {
+        match self {
+            Blindfold::Off => "off",
+            Blindfold::HideAll => "hide_all",
+            Blindfold::Discs => "discs",
+            Blindfold::HideOpponent => "hide_opponent",
+            Blindfold::Fade => "fade",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Blindfold>
+    {
+        ALL_BLINDFOLD_MODES.iter().cloned().find(|mode| mode.key() == key)
+    }
+}
+
+/// The opacity of pieces shown for the given time, when they fade out after the given delay.
+pub fn fading_opacity(delay: Duration, shown_for: Duration) -> f64
+{
+    if shown_for <= delay {
+        1.0
+    }
+    else {
+        (1.0 - (shown_for - delay).as_secs_f64() / FADE_DURATION.as_secs_f64()).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn keys()
+    {
+        ALL_BLINDFOLD_MODES.iter().for_each(|mode| {
+            assert_eq!(Blindfold::from_key(mode.key()), Some(*mode));
+        });
+        assert_eq!(Blindfold::from_key("hidden"), None);
+    }
+
+    #[test]
+    fn pieces_fade_out_after_the_delay()
+    {
+        let delay = Duration::from_secs(5);
+        assert_eq!(fading_opacity(delay, Duration::from_secs(0)), 1.0);
+        assert_eq!(fading_opacity(delay, delay), 1.0);
+        let half_faded = fading_opacity(delay, delay + FADE_DURATION / 2);
+        assert!((half_faded - 0.5).abs() < 1e-9);
+        assert_eq!(fading_opacity(delay, delay + FADE_DURATION), 0.0);
+        assert_eq!(fading_opacity(delay, delay + FADE_DURATION * 3), 0.0);
+        assert_eq!(fading_opacity(Duration::from_secs(0), FADE_DURATION), 0.0);
+    }
+}
\ No newline at end of file
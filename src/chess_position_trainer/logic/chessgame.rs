@@ -3,11 +3,22 @@ use pleco::core::{Piece, Player, PieceType};
 use pleco::core::sq::{SQ};
 use pleco::core::piece_move::{BitMove};
 use pleco::core::move_list::{MoveList};
+use super::notation::{parse_move, move_to_san, uci_line_to_san};
+use super::move_outcome::{MoveOutcome, MoveError};
+use super::game_result::{GameResult, WinReason, DrawReason};
+use super::pgn::{write_pgn};
+use super::variation_tree::{VariationTree, Navigation};
+use super::annotation::{Annotation, annotations_to_pgn};
+use super::material::MaterialBalance;
 
 #[derive(Clone)]
 pub struct ChessGame
 {
     position: Board,
+    tree: VariationTree,
+    current_node: Option<usize>,
+    result: Option<GameResult>,
+    declared_result: Option<(Option<usize>, GameResult)>,
 }
 
 impl ChessGame
@@ -15,11 +26,17 @@ impl ChessGame
     pub fn new_from_fen(position_str: &str) -> Option<ChessGame>
     {
         match Board::from_fen(position_str) {
-            Ok(board) => Some(
-                ChessGame{
+            Ok(board) => {
+                let mut game = ChessGame{
+                    tree: VariationTree::new(&board.fen(), board.zobrist()),
                     position: board,
-                }
-            ),
+                    current_node: None,
+                    result: None,
+                    declared_result: None,
+                };
+                game.result = game.result_by_rules();
+                Some(game)
+            },
             _ => None
         }
     }
@@ -29,50 +46,553 @@ impl ChessGame
         self.position.turn() == Player::White
     }
 
+    /// True if the game is not over and the given side has to play.
+    pub fn waits_for(&self, side: Player) -> bool
+    {
+        self.result.is_none() && self.position.turn() == side
+    }
+
     pub fn piece_at_cell(&self, cell: SQ) -> Piece
     {
         self.position.piece_at_sq(cell)
     }
 
-    pub fn is_legal_move(&self, start_cell: SQ, end_cell: SQ) -> bool {
-        match self.get_matching_move(start_cell, end_cell, PieceType::None, true) {
-            Some(_) => true,
-            None => false
+    pub fn get_fen(&self) -> String
+    {
+        self.position.fen()
+    }
+
+    pub fn get_start_fen(&self) -> &str
+    {
+        self.tree.get_start_fen()
+    }
+
+    /// The pieces of the current position, for each of the 64 cells.
+    pub fn get_pieces(&self) -> Vec<Piece>
+    {
+        board_pieces(&self.position)
+    }
+
+    /// The captures and the material balance since the start position.
+    pub fn material_balance(&self) -> MaterialBalance
+    {
+        let current_pieces = self.get_pieces();
+        let start_pieces = match Board::from_fen(self.get_start_fen()) {
+            Ok(start_board) => board_pieces(&start_board),
+            Err(_) => current_pieces.clone()
+        };
+        MaterialBalance::between(&start_pieces, &current_pieces)
+    }
+
+    /// The moves leading from the start position to the current one.
+    pub fn get_history(&self) -> Vec<MoveOutcome>
+    {
+        self.tree.path_to(self.current_node).iter()
+            .map(|node| self.tree.get_node(*node).outcome.clone())
+            .collect()
+    }
+
+    /// The move which led to the current position, if any.
+    pub fn get_last_move(&self) -> Option<MoveOutcome>
+    {
+        self.current_node.map(|node| self.tree.get_node(node).outcome.clone())
+    }
+
+    /// The arrows and circles drawn on the current position.
+    pub fn get_annotations(&self) -> Vec<Annotation>
+    {
+        self.tree.get_annotations(self.current_node).to_vec()
+    }
+
+    pub fn toggle_annotation(&mut self, annotation: Annotation)
+    {
+        self.tree.toggle_annotation(self.current_node, annotation);
+    }
+
+    /// The cell of the king of the side to move, if that king is in check.
+    pub fn checked_king_cell(&self) -> Option<SQ>
+    {
+        if self.position.in_check() {
+            Some(self.position.king_sq(self.position.turn()))
+        }
+        else { None }
+    }
+
+    pub fn uci_line_to_san(&self, uci_moves: &[String]) -> Vec<String>
+    {
+        uci_line_to_san(&self.position, uci_moves)
+    }
+
+    pub fn get_variation_tree(&self) -> &VariationTree
+    {
+        &self.tree
+    }
+
+    pub fn get_current_node(&self) -> Option<usize>
+    {
+        self.current_node
+    }
+
+    /// Goes to another position of the variation tree.
+    /// Returns false if the position did not change.
+    pub fn navigate(&mut self, target: Navigation) -> bool
+    {
+        let target_node = match target {
+            Navigation::Start => None,
+            Navigation::Back => match self.current_node {
+                Some(node) => self.tree.get_node(node).parent,
+                None => return false
+            },
+            Navigation::Forward => match self.tree.get_children(self.current_node).first() {
+                Some(child) => Some(*child),
+                None => return false
+            },
+            Navigation::End => self.tree.main_line_end(self.current_node),
+            Navigation::Node(node) => Some(node),
+        };
+        if target_node == self.current_node {
+            return false;
+        }
+
+        self.position = Board::from_fen(self.tree.get_fen(target_node))
+            .expect("Failed to restore a position of the variation tree !");
+        self.current_node = target_node;
+        self.result = match self.declared_result {
+            Some((node, result)) if node == target_node => Some(result),
+            _ => self.result_by_rules()
+        };
+        true
+    }
+
+    /// The result of the game, if it is over, either by the rules or by a
+    /// resignation or a draw agreement.
+    pub fn get_result(&self) -> Option<GameResult>
+    {
+        self.result
+    }
+
+    fn result_by_rules(&self) -> Option<GameResult>
+    {
+        let no_legal_move = self.position.generate_moves().is_empty();
+        if no_legal_move && self.position.in_check() {
+            Some(if self.is_white_turn() {
+                GameResult::BlackWins(WinReason::Checkmate)
+            } else {
+                GameResult::WhiteWins(WinReason::Checkmate)
+            })
+        }
+        else if no_legal_move {
+            Some(GameResult::Draw(DrawReason::Stalemate))
         }
+        else { None }
     }
 
-    pub fn is_promotion_move(&self, start_cell: SQ, end_cell: SQ) -> bool {
-        let move_to_test = self.get_matching_move(start_cell, end_cell, PieceType::None, true);
-        match move_to_test {
-            Some(move_to_test) => move_to_test.is_promo(),
-            None => false
+    pub fn pgn_result_tag(&self) -> &'static str
+    {
+        match self.result {
+            Some(result) => result.pgn_tag(),
+            None => "*"
         }
     }
 
-    pub fn do_move(&mut self, start_cell: SQ, end_cell: SQ, promotion: PieceType) {
-        if let Some(move_to_execute) = self.get_matching_move(start_cell, end_cell, promotion, false) {
-            self.position.apply_move(move_to_execute);
+    pub fn to_pgn(&self) -> String
+    {
+        let start_comment = annotations_to_pgn(self.tree.get_annotations(None));
+        let moves: Vec<(String, String)> = self.tree.path_to(self.current_node).iter()
+            .map(|node| (
+                self.tree.get_node(*node).outcome.san.clone(),
+                annotations_to_pgn(self.tree.get_annotations(Some(*node))),
+            ))
+            .collect();
+        write_pgn(self.get_start_fen(), &start_comment, &moves, self.pgn_result_tag())
+    }
+
+    fn declare_result(&mut self, result: GameResult)
+    {
+        self.result = Some(result);
+        self.declared_result = Some((self.current_node, result));
+    }
+
+    pub fn resign(&mut self, white_resigns: bool)
+    {
+        if self.result.is_none() {
+            self.declare_result(if white_resigns {
+                GameResult::BlackWins(WinReason::Resignation)
+            } else {
+                GameResult::WhiteWins(WinReason::Resignation)
+            });
         }
     }
 
+    pub fn agree_draw(&mut self)
+    {
+        if self.result.is_none() {
+            self.declare_result(GameResult::Draw(DrawReason::Agreement));
+        }
+    }
+
+    /// The rule which would allow a draw claim in the current position, if any.
+    pub fn claimable_draw(&self) -> Option<DrawReason>
+    {
+        let current_key = self.position.zobrist();
+        let occurrences = self.tree.keys_to(self.current_node).iter()
+            .filter(|key| **key == current_key)
+            .count();
+
+        if self.position.rule_50() >= 100 {
+            Some(DrawReason::FiftyMoves)
+        }
+        else if occurrences >= 3 {
+            Some(DrawReason::Repetition)
+        }
+        else { None }
+    }
+
+    pub fn claim_draw(&mut self) -> Result<GameResult, String>
+    {
+        if self.result.is_some() {
+            return Err(String::from("The game is already over !"));
+        }
+        match self.claimable_draw() {
+            Some(reason) => {
+                let result = GameResult::Draw(reason);
+                self.declare_result(result);
+                Ok(result)
+            },
+            None => Err(String::from("Neither the fifty-move rule nor the threefold repetition applies !"))
+        }
+    }
+
+    /// The cells where the piece on start_cell can legally go, each one with
+    /// a flag telling whether the move captures.
+    /// A castling king goes to its final cell, not to the rook cell.
+    pub fn legal_destinations(&self, start_cell: SQ) -> Vec<(SQ, bool)>
+    {
+        if self.result.is_some() {
+            return vec![];
+        }
+        let mut destinations: Vec<(SQ, bool)> = vec![];
+        self.position.generate_moves().iter()
+            .filter(|m| m.get_src() == start_cell)
+            .for_each(|m| {
+                let destination = (king_destination(*m), m.is_capture());
+                if ! destinations.contains(&destination) {
+                    destinations.push(destination);
+                }
+            });
+        destinations
+    }
+
+    /// Plays the move going from start_cell to end_cell.
+    /// For a promotion move, PieceType::None as promotion gives a MissingPromotion error,
+    /// so that the caller can ask for the promotion piece and try again.
+    pub fn do_move(&mut self, start_cell: SQ, end_cell: SQ, promotion: PieceType) -> Result<MoveOutcome, MoveError> {
+        if self.result.is_some() {
+            return Err(MoveError::GameOver);
+        }
+        let move_to_execute = self.get_matching_move(start_cell, end_cell, promotion)?;
+        Ok(self.apply(move_to_execute))
+    }
+
+    /// Plays the move typed either in SAN or in UCI.
+    pub fn do_typed_move(&mut self, move_text: &str) -> Result<MoveOutcome, MoveError> {
+        if self.result.is_some() {
+            return Err(MoveError::GameOver);
+        }
+        let move_to_execute = parse_move(&self.position, move_text)?;
+        Ok(self.apply(move_to_execute))
+    }
+
+    fn apply(&mut self, move_to_execute: BitMove) -> MoveOutcome {
+        let captured = if move_to_execute.is_capture() {
+            self.position.captured_piece(move_to_execute)
+        } else { PieceType::None };
+        let promotion = if move_to_execute.is_promo() {
+            move_to_execute.promo_piece()
+        } else { PieceType::None };
+        let san = move_to_san(&self.position, move_to_execute);
+
+        self.position.apply_move(move_to_execute);
+        self.result = self.result_by_rules();
+
+        let outcome = MoveOutcome {
+            start_cell: move_to_execute.get_src(),
+            end_cell: king_destination(move_to_execute),
+            promotion,
+            captured,
+            san,
+            uci: move_to_execute.stringify(),
+            gives_check: self.position.in_check(),
+            gives_mate: matches!(self.result,
+                Some(GameResult::WhiteWins(WinReason::Checkmate)) |
+                Some(GameResult::BlackWins(WinReason::Checkmate))),
+        };
+
+        let node = match self.tree.find_child(self.current_node, &outcome.uci) {
+            Some(node) => node,
+            None => self.tree.add_child(
+                self.current_node,
+                outcome.clone(),
+                self.position.fen(),
+                self.position.zobrist(),
+            )
+        };
+        self.current_node = Some(node);
+        outcome
+    }
+
     fn get_matching_move(&self, start_cell: SQ, end_cell: SQ,
-         expected_promotion: PieceType, skip_promotion_test: bool) -> Option<BitMove> {
-        let position: Board = self.position.shallow_clone();
-        let legal_moves_list: MoveList = position.generate_moves();
-
-        let expected_moves: Vec<&BitMove> = legal_moves_list.iter().filter(|m| {            
-            let mut test = m.get_src() == start_cell &&
-                m.get_dest() == end_cell;
-            if m.is_promo() && !skip_promotion_test {
-                test &= m.promo_piece() == expected_promotion;
-            }
+         expected_promotion: PieceType) -> Result<BitMove, MoveError> {
+        let legal_moves_list: MoveList = self.position.generate_moves();
 
-            test
+        let expected_moves: Vec<&BitMove> = legal_moves_list.iter().filter(|m| {
+            m.get_src() == start_cell && (m.get_dest() == end_cell || king_destination(**m) == end_cell)
         }).collect::<Vec<&BitMove>>();
 
-        if expected_moves.len() > 0 { 
-            let the_move = expected_moves[0].clone();
-            Some(the_move)
-        } else { None }
+        let move_name = format!("{}{}", start_cell.to_string(), end_cell.to_string());
+
+        if expected_moves.is_empty() {
+            return Err(MoveError::Illegal(move_name));
+        }
+
+        if expected_moves[0].is_promo() {
+            if expected_promotion == PieceType::None {
+                return Err(MoveError::MissingPromotion(move_name));
+            }
+            match expected_moves.iter().find(|m| m.promo_piece() == expected_promotion) {
+                Some(the_move) => Ok(**the_move),
+                None => Err(MoveError::Illegal(move_name))
+            }
+        }
+        else if expected_moves.len() > 1 {
+            let candidates = expected_moves.iter().map(|m| move_to_san(&self.position, **m)).collect();
+            Err(MoveError::Ambiguous(move_name, candidates))
+        }
+        else {
+            Ok(*expected_moves[0])
+        }
+    }
+}
+
+/// The pieces of the board, for each of the 64 cells.
+pub fn board_pieces(board: &Board) -> Vec<Piece>
+{
+    (0..64u8).map(|cell| board.piece_at_sq(SQ::from(cell))).collect()
+}
+
+/// Whether the piece could go from start_cell to end_cell, the other pieces being ignored
+/// as they may have moved before the move is played : checks the premoves.
+/// A castling king may go to its final cell or to the rook cell.
+pub fn can_reach(piece: Piece, start_cell: SQ, end_cell: SQ) -> bool
+{
+    if start_cell == end_cell {
+        return false;
+    }
+    let file_move = end_cell.file_idx_of_sq() as i32 - start_cell.file_idx_of_sq() as i32;
+    let rank_move = end_cell.rank_idx_of_sq() as i32 - start_cell.rank_idx_of_sq() as i32;
+    match piece.type_of() {
+        PieceType::P => {
+            let (forward, pawns_rank) = if piece.player() == Some(Player::White) { (1, 1) } else { (-1, 6) };
+            (rank_move == forward && file_move.abs() <= 1)
+                || (rank_move == 2 * forward && file_move == 0 && start_cell.rank_idx_of_sq() == pawns_rank)
+        },
+        PieceType::N => file_move.abs() * rank_move.abs() == 2,
+        PieceType::B => file_move.abs() == rank_move.abs(),
+        PieceType::R => file_move == 0 || rank_move == 0,
+        PieceType::Q => file_move.abs() == rank_move.abs() || file_move == 0 || rank_move == 0,
+        PieceType::K => {
+            let home_rank = if piece.player() == Some(Player::White) { 0 } else { 7 };
+            let castling = start_cell.file_idx_of_sq() == 4 && start_cell.rank_idx_of_sq() == home_rank
+                && rank_move == 0 && [-4, -2, 2, 3].contains(&file_move);
+            (file_move.abs() <= 1 && rank_move.abs() <= 1) || castling
+        },
+        _ => false
+    }
+}
+
+/// The destination of the move, except for castling moves
+/// for which it is the cell reached by the king.
+fn king_destination(the_move: BitMove) -> SQ
+{
+    let src = the_move.get_src();
+    if the_move.is_king_castle() {
+        SQ::from(6 + 8 * src.rank_idx_of_sq())
+    }
+    else if the_move.is_queen_castle() {
+        SQ::from(2 + 8 * src.rank_idx_of_sq())
+    }
+    else {
+        the_move.get_dest()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const CASTLES: &str = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    const PROMOTION: &str = "8/4P1k1/8/8/8/8/6K1/8 w - - 0 1";
+
+    fn game(fen: &str) -> ChessGame
+    {
+        ChessGame::new_from_fen(fen).expect("Bad test position !")
+    }
+
+    #[test]
+    fn quiet_move_outcome()
+    {
+        let mut game = game("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let outcome = game.do_move(SQ::G1, SQ::F3, PieceType::None).unwrap();
+        assert_eq!(outcome, MoveOutcome {
+            start_cell: SQ::G1,
+            end_cell: SQ::F3,
+            promotion: PieceType::None,
+            captured: PieceType::None,
+            san: String::from("Nf3"),
+            uci: String::from("g1f3"),
+            gives_check: false,
+            gives_mate: false,
+        });
+        assert!(! game.is_white_turn());
+        assert_eq!(game.get_last_move(), Some(outcome));
+    }
+
+    #[test]
+    fn captures()
+    {
+        let mut game = game("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2");
+        let outcome = game.do_move(SQ::E4, SQ::D5, PieceType::None).unwrap();
+        assert_eq!((outcome.captured, outcome.san.as_str(), outcome.uci.as_str()), (PieceType::P, "exd5", "e4d5"));
+
+        let mut en_passant = self::game("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+        let outcome = en_passant.do_move(SQ::E5, SQ::D6, PieceType::None).unwrap();
+        assert_eq!((outcome.captured, outcome.san.as_str(), outcome.end_cell), (PieceType::P, "exd6", SQ::D6));
+        assert_eq!(en_passant.piece_at_cell(SQ::D5), Piece::None);
+    }
+
+    #[test]
+    fn castling_ends_on_the_king_cell()
+    {
+        let mut king_side = game(CASTLES);
+        let outcome = king_side.do_move(SQ::E1, SQ::G1, PieceType::None).unwrap();
+        assert_eq!((outcome.start_cell, outcome.end_cell, outcome.san.as_str()), (SQ::E1, SQ::G1, "O-O"));
+        assert_eq!(king_side.piece_at_cell(SQ::F1), Piece::WhiteRook);
+
+        let mut on_the_rook = game(CASTLES);
+        let outcome = on_the_rook.do_move(SQ::E1, SQ::H1, PieceType::None).unwrap();
+        assert_eq!((outcome.end_cell, outcome.san.as_str()), (SQ::G1, "O-O"));
+
+        let mut queen_side = game(CASTLES);
+        let outcome = queen_side.do_move(SQ::E1, SQ::C1, PieceType::None).unwrap();
+        assert_eq!((outcome.end_cell, outcome.san.as_str()), (SQ::C1, "O-O-O"));
+        assert_eq!(queen_side.piece_at_cell(SQ::D1), Piece::WhiteRook);
+    }
+
+    #[test]
+    fn promotion_outcome()
+    {
+        let mut game = game(PROMOTION);
+        let outcome = game.do_move(SQ::E7, SQ::E8, PieceType::N).unwrap();
+        assert_eq!((outcome.promotion, outcome.san.as_str(), outcome.uci.as_str()), (PieceType::N, "e8=N+", "e7e8n"));
+        assert!(outcome.gives_check);
+        assert_eq!(game.piece_at_cell(SQ::E8), Piece::WhiteKnight);
+    }
+
+    #[test]
+    fn check_and_mate()
+    {
+        let mut check = game("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+        let outcome = check.do_move(SQ::A1, SQ::A8, PieceType::None).unwrap();
+        assert!(outcome.gives_check && ! outcome.gives_mate);
+        assert_eq!(check.checked_king_cell(), Some(SQ::E8));
+        assert_eq!(check.get_result(), None);
+
+        let mut mate = game("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let outcome = mate.do_move(SQ::A1, SQ::A8, PieceType::None).unwrap();
+        assert!(outcome.gives_check && outcome.gives_mate);
+        assert_eq!(outcome.san, "Ra8#");
+        assert_eq!(mate.get_result(), Some(GameResult::WhiteWins(WinReason::Checkmate)));
+    }
+
+    #[test]
+    fn move_errors()
+    {
+        let mut game = game(PROMOTION);
+        assert_eq!(game.do_move(SQ::E7, SQ::E6, PieceType::None), Err(MoveError::Illegal(String::from("e7e6"))));
+        assert_eq!(game.do_move(SQ::E7, SQ::E8, PieceType::None), Err(MoveError::MissingPromotion(String::from("e7e8"))));
+        assert_eq!(game.do_move(SQ::E7, SQ::E8, PieceType::K), Err(MoveError::Illegal(String::from("e7e8"))));
+        assert_eq!(game.do_typed_move(""), Err(MoveError::Empty));
+        assert_eq!(game.do_typed_move("K9"), Err(MoveError::Malformed(String::from("K9"))));
+        assert_eq!(self::game("4k3/8/8/8/8/8/8/1N3N1K w - - 0 1").do_typed_move("Nd2"), Err(MoveError::Ambiguous(
+            String::from("Nd2"),
+            vec![String::from("Nbd2"), String::from("Nfd2")]
+        )));
+        // A failed move leaves the game as it was.
+        assert_eq!(game.get_fen(), PROMOTION);
+        assert_eq!(game.get_last_move(), None);
+    }
+
+    #[test]
+    fn no_move_once_the_game_is_over()
+    {
+        let mut mate = game("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        mate.do_move(SQ::A1, SQ::A8, PieceType::None).unwrap();
+        assert_eq!(mate.do_move(SQ::G8, SQ::H8, PieceType::None), Err(MoveError::GameOver));
+        assert_eq!(mate.do_typed_move("Kh8"), Err(MoveError::GameOver));
+        assert_eq!(mate.legal_destinations(SQ::G8), vec![]);
+
+        let mut resigned = game(PROMOTION);
+        resigned.resign(true);
+        assert_eq!(resigned.do_move(SQ::E7, SQ::E8, PieceType::Q), Err(MoveError::GameOver));
+    }
+
+    #[test]
+    fn waits_for_the_side_to_move_after_navigation()
+    {
+        let mut game = game("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        game.do_typed_move("e4").unwrap();
+        game.do_typed_move("e5").unwrap();
+        assert!(game.waits_for(Player::White));
+        // Going back to the computer's turn must let it play again.
+        assert!(game.navigate(Navigation::Back));
+        assert!(game.waits_for(Player::Black) && ! game.waits_for(Player::White));
+        assert!(game.navigate(Navigation::Start));
+        assert!(game.waits_for(Player::White));
+
+        let mut mate = self::game("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        mate.do_typed_move("Ra8").unwrap();
+        assert!(! mate.waits_for(Player::Black));
+        assert!(mate.navigate(Navigation::Back));
+        assert!(mate.waits_for(Player::White));
+    }
+
+    #[test]
+    fn reachable_premove_cells()
+    {
+        assert!(can_reach(Piece::WhitePawn, SQ::E2, SQ::E4));
+        assert!(can_reach(Piece::WhitePawn, SQ::E2, SQ::D3));
+        assert!(! can_reach(Piece::WhitePawn, SQ::E3, SQ::E5));
+        assert!(! can_reach(Piece::WhitePawn, SQ::E2, SQ::E1));
+        assert!(can_reach(Piece::BlackPawn, SQ::D7, SQ::D5));
+        assert!(can_reach(Piece::BlackPawn, SQ::D2, SQ::C1));
+        assert!(! can_reach(Piece::BlackPawn, SQ::D2, SQ::D3));
+
+        assert!(can_reach(Piece::WhiteKnight, SQ::G1, SQ::F3));
+        assert!(! can_reach(Piece::WhiteKnight, SQ::G1, SQ::G3));
+        assert!(can_reach(Piece::BlackBishop, SQ::C8, SQ::H3));
+        assert!(! can_reach(Piece::BlackBishop, SQ::C8, SQ::C3));
+        assert!(can_reach(Piece::WhiteRook, SQ::A1, SQ::A8));
+        assert!(! can_reach(Piece::WhiteRook, SQ::A1, SQ::B2));
+        assert!(can_reach(Piece::BlackQueen, SQ::D8, SQ::H4));
+        assert!(! can_reach(Piece::BlackQueen, SQ::D8, SQ::E6));
+
+        assert!(can_reach(Piece::WhiteKing, SQ::E1, SQ::F2));
+        assert!(can_reach(Piece::WhiteKing, SQ::E1, SQ::G1));
+        assert!(can_reach(Piece::WhiteKing, SQ::E1, SQ::H1));
+        assert!(can_reach(Piece::BlackKing, SQ::E8, SQ::C8));
+        assert!(can_reach(Piece::BlackKing, SQ::E8, SQ::A8));
+        assert!(! can_reach(Piece::BlackKing, SQ::E7, SQ::G7));
+        assert!(! can_reach(Piece::WhiteKing, SQ::E1, SQ::E1));
     }
 }
\ No newline at end of file
@@ -0,0 +1,67 @@
+/// Where the files letters and the ranks digits are written.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CoordinatesPlacement
+{
+    Off,
+    OutsideBottomLeft,
+    OutsideAllSides,
+    /// In the cells of the bottom rank and of the left file, as on lichess.
+    InsideCorners,
+}
+
+pub const ALL_COORDINATES_PLACEMENTS: [CoordinatesPlacement; 4] = [
+    CoordinatesPlacement::OutsideAllSides,
+    CoordinatesPlacement::OutsideBottomLeft,
+    CoordinatesPlacement::InsideCorners,
+    CoordinatesPlacement::Off,
+];
+
+impl CoordinatesPlacement
+{
+    pub fn label(&self) -> &'static str
+    {
+        match self {
+            CoordinatesPlacement::Off => "Hidden",
+            CoordinatesPlacement::OutsideBottomLeft => "Outside, bottom and left",
+            CoordinatesPlacement::OutsideAllSides => "Outside, all sides",
+            CoordinatesPlacement::InsideCorners => "Inside the border cells",
+        }
+    }
+
+    /// The name saved in the preferences.
+    pub fn key(&self) -> &'static str
+    {
+        match self {
+            CoordinatesPlacement::Off => "off",
+            CoordinatesPlacement::OutsideBottomLeft => "bottom_left",
+            CoordinatesPlacement::OutsideAllSides => "all_sides",
+            CoordinatesPlacement::InsideCorners => "inside",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<CoordinatesPlacement>
+    {
+        ALL_COORDINATES_PLACEMENTS.iter().cloned().find(|placement| placement.key() == key)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CoordinatesOptions
+{
+    pub placement: CoordinatesPlacement,
+    pub uppercase: bool,
+    /// Written with the cells colours of the theme rather than its coordinates colour.
+    pub cells_colours: bool,
+}
+
+impl Default for CoordinatesOptions
+{
+    fn default() -> CoordinatesOptions
+    {
+        CoordinatesOptions {
+            placement: CoordinatesPlacement::OutsideAllSides,
+            uppercase: true,
+            cells_colours: false,
+        }
+    }
+}
\ No newline at end of file
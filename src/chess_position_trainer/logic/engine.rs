@@ -0,0 +1,454 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+
+const MATE_SCORE: i32 = 100_000;
+
+/// Score given by the engine, from the point of view of the side to move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EngineScore
+{
+    Centipawns(i32),
+    MateIn(i32),
+}
+
+impl EngineScore
+{
+    pub fn as_centipawns(&self) -> i32
+    {
+        match self {
+            EngineScore::Centipawns(value) => *value,
+            EngineScore::MateIn(moves) if *moves > 0 => MATE_SCORE - moves,
+            EngineScore::MateIn(moves) => -MATE_SCORE - moves,
+        }
+    }
+
+    pub fn negated(&self) -> EngineScore
+    {
+        match self {
+            EngineScore::Centipawns(value) => EngineScore::Centipawns(-value),
+            EngineScore::MateIn(moves) => EngineScore::MateIn(-moves),
+        }
+    }
+}
+
+/// Why a search was requested, given back with its result.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchPurpose
+{
+    PlayMove,
+    AnswerDrawOffer,
+    Analyse,
+    /// Reads what the tablebases tell of the position.
+    ProbeTablebase,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EngineReply
+{
+    SearchDone {
+        purpose: SearchPurpose,
+        fen: String,
+        best_move: String,
+        score: Option<EngineScore>,
+        principal_variation: Vec<String>,
+        tablebase_hits: u64,
+    },
+    Failure(String),
+}
+
+enum EngineRequest
+{
+    Search {
+        purpose: SearchPurpose,
+        fen: String,
+        movetime_ms: u32,
+    },
+    /// Drops the searches of this purpose asked before, and not started yet.
+    Cancel {
+        purpose: SearchPurpose,
+    },
+    Quit,
+}
+
+/// When does the engine accept a draw offer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawOfferPolicy
+{
+    /// The engine declines as soon as it thinks it is better by more than this.
+    pub max_engine_advantage_cp: i32,
+    /// The engine declines before this number of moves has been played.
+    pub min_moves_played: u32,
+}
+
+impl Default for DrawOfferPolicy
+{
+    fn default() -> DrawOfferPolicy
+    {
+        DrawOfferPolicy {
+            max_engine_advantage_cp: 30,
+            min_moves_played: 0,
+        }
+    }
+}
+
+impl DrawOfferPolicy
+{
+    pub fn accepts(&self, engine_score: EngineScore, moves_played: u32) -> bool
+    {
+        moves_played >= self.min_moves_played &&
+            engine_score.as_centipawns() <= self.max_engine_advantage_cp
+    }
+}
+
+/// An UCI engine running in its own thread, so that the interface
+/// is never blocked while it is thinking.
+pub struct EngineOpponent
+{
+    requests: Sender<EngineRequest>,
+    replies: Receiver<EngineReply>,
+}
+
+impl EngineOpponent
+{
+    /// The tablebases folder is given to the engine unless it is empty.
+    pub fn start(engine_path: &str, syzygy_path: &str) -> Result<EngineOpponent, String>
+    {
+        let mut child = Command::new(engine_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Could not start the engine {} ({})", engine_path, e))?;
+
+        let input = child.stdin.take().ok_or("Could not talk to the engine !")?;
+        let output = child.stdout.take().ok_or("Could not listen to the engine !")?;
+
+        let (requests_sender, requests_receiver) = channel();
+        let (replies_sender, replies_receiver) = channel();
+        let syzygy_path = syzygy_path.to_string();
+
+        thread::spawn(move || {
+            let mut session = EngineSession {
+                child,
+                input,
+                output: BufReader::new(output),
+            };
+            if let Err(e) = session.run(&syzygy_path, requests_receiver, &replies_sender) {
+                let _ = replies_sender.send(EngineReply::Failure(e));
+            }
+            session.stop();
+        });
+
+        Ok(EngineOpponent {
+            requests: requests_sender,
+            replies: replies_receiver,
+        })
+    }
+
+    pub fn request_search(&self, purpose: SearchPurpose, fen: &str, movetime_ms: u32)
+    {
+        let _ = self.requests.send(EngineRequest::Search {
+            purpose,
+            fen: fen.to_string(),
+            movetime_ms,
+        });
+    }
+
+    /// The search already started still gives its reply, which the caller must ignore.
+    pub fn cancel_searches(&self, purpose: SearchPurpose)
+    {
+        let _ = self.requests.send(EngineRequest::Cancel { purpose });
+    }
+
+    /// Gives the next reply of the engine without waiting for it.
+    pub fn try_reply(&self) -> Option<EngineReply>
+    {
+        match self.replies.try_recv() {
+            Ok(reply) => Some(reply),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(EngineReply::Failure(String::from("The engine has stopped !"))),
+        }
+    }
+}
+
+impl Drop for EngineOpponent
+{
+    fn drop(&mut self)
+    {
+        let _ = self.requests.send(EngineRequest::Quit);
+    }
+}
+
+struct EngineSession
+{
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+}
+
+impl EngineSession
+{
+    fn run(&mut self, syzygy_path: &str, requests: Receiver<EngineRequest>, replies: &Sender<EngineReply>) -> Result<(), String>
+    {
+        self.send("uci")?;
+        self.wait_for("uciok")?;
+        if ! syzygy_path.is_empty() {
+            self.send(&format!("setoption name SyzygyPath value {}", syzygy_path))?;
+        }
+        self.send("isready")?;
+        self.wait_for("readyok")?;
+
+        let mut pending: Vec<EngineRequest> = vec![];
+        loop {
+            if pending.is_empty() {
+                match requests.recv() {
+                    Ok(request) => pending.push(request),
+                    Err(_) => break
+                }
+            }
+            pending.extend(requests.try_iter());
+            apply_cancellations(&mut pending);
+            drop_outdated_analyses(&mut pending);
+            if pending.is_empty() {
+                continue;
+            }
+
+            match pending.remove(0) {
+                EngineRequest::Search { purpose, fen, movetime_ms } => {
+                    self.send(&format!("position fen {}", fen))?;
+                    self.send(&format!("go movetime {}", movetime_ms))?;
+                    let (best_move, score, principal_variation, tablebase_hits) = self.read_search_result()?;
+                    let reply = EngineReply::SearchDone {
+                        purpose,
+                        fen,
+                        best_move,
+                        score,
+                        principal_variation,
+                        tablebase_hits,
+                    };
+                    if replies.send(reply).is_err() {
+                        break;
+                    }
+                },
+                EngineRequest::Cancel { .. } => {},
+                EngineRequest::Quit => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn stop(&mut self)
+    {
+        let _ = self.send("quit");
+        let _ = self.child.wait();
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), String>
+    {
+        writeln!(self.input, "{}", command)
+            .and_then(|_| self.input.flush())
+            .map_err(|e| format!("Could not talk to the engine ({})", e))
+    }
+
+    fn read_line(&mut self) -> Result<String, String>
+    {
+        let mut line = String::new();
+        match self.output.read_line(&mut line) {
+            Ok(0) => Err(String::from("The engine has stopped !")),
+            Ok(_) => Ok(line.trim().to_string()),
+            Err(e) => Err(format!("Could not listen to the engine ({})", e)),
+        }
+    }
+
+    fn wait_for(&mut self, expected: &str) -> Result<(), String>
+    {
+        loop {
+            if self.read_line()? == expected {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_search_result(&mut self) -> Result<(String, Option<EngineScore>, Vec<String>, u64), String>
+    {
+        let mut score = None;
+        let mut principal_variation = vec![];
+        let mut tablebase_hits = 0;
+        loop {
+            let line = self.read_line()?;
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.first() {
+                Some(&"info") => {
+                    if let Some(new_score) = parse_score(&tokens) {
+                        score = Some(new_score);
+                    }
+                    if let Some(hits) = parse_tablebase_hits(&tokens) {
+                        tablebase_hits = hits;
+                    }
+                    if let Some(pv_index) = tokens.iter().position(|token| *token == "pv") {
+                        principal_variation = tokens[pv_index + 1..].iter()
+                            .map(|token| token.to_string())
+                            .collect();
+                    }
+                },
+                Some(&"bestmove") => {
+                    let best_move = tokens.get(1).ok_or("The engine gave no move !")?;
+                    return Ok((best_move.to_string(), score, principal_variation, tablebase_hits));
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Only the latest analysis is worth doing, as the previous ones are about
+/// positions the user has already left.
+fn drop_outdated_analyses(pending: &mut Vec<EngineRequest>)
+{
+    let is_analysis = |request: &EngineRequest| matches!(request,
+        EngineRequest::Search { purpose: SearchPurpose::Analyse, .. });
+
+    if let Some(last_analysis) = pending.iter().rposition(is_analysis) {
+        let mut index = 0;
+        pending.retain(|request| {
+            let keep = index == last_analysis || ! is_analysis(request);
+            index += 1;
+            keep
+        });
+    }
+}
+
+/// Removes the cancellations, with the searches they cancel.
+fn apply_cancellations(pending: &mut Vec<EngineRequest>)
+{
+    while let Some(cancel_index) = pending.iter().position(|request| matches!(request, EngineRequest::Cancel { .. })) {
+        if let EngineRequest::Cancel { purpose: cancelled_purpose } = pending.remove(cancel_index) {
+            let mut index = 0;
+            pending.retain(|request| {
+                let cancelled = index < cancel_index && matches!(request,
+                    EngineRequest::Search { purpose, .. } if *purpose == cancelled_purpose);
+                index += 1;
+                ! cancelled
+            });
+        }
+    }
+}
+
+fn parse_score(tokens: &[&str]) -> Option<EngineScore>
+{
+    let score_index = tokens.iter().position(|token| *token == "score")?;
+    let kind = tokens.get(score_index + 1)?;
+    let value = tokens.get(score_index + 2)?.parse::<i32>().ok()?;
+    match *kind {
+        "cp" => Some(EngineScore::Centipawns(value)),
+        "mate" => Some(EngineScore::MateIn(value)),
+        _ => None
+    }
+}
+
+fn parse_tablebase_hits(tokens: &[&str]) -> Option<u64>
+{
+    let hits_index = tokens.iter().position(|token| *token == "tbhits")?;
+    tokens.get(hits_index + 1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn tokens(line: &str) -> Vec<&str>
+    {
+        line.split_whitespace().collect()
+    }
+
+    #[test]
+    fn scores()
+    {
+        assert_eq!(parse_score(&tokens("info depth 12 score cp 35 nodes 1000 pv e2e4")), Some(EngineScore::Centipawns(35)));
+        assert_eq!(parse_score(&tokens("info depth 20 score cp -120 lowerbound")), Some(EngineScore::Centipawns(-120)));
+        assert_eq!(parse_score(&tokens("info depth 30 score mate 3 pv h5f7")), Some(EngineScore::MateIn(3)));
+        assert_eq!(parse_score(&tokens("info depth 30 score mate -2")), Some(EngineScore::MateIn(-2)));
+        assert_eq!(parse_score(&tokens("info depth 5 nodes 300")), None);
+        assert_eq!(parse_score(&tokens("info score cp")), None);
+        assert_eq!(parse_score(&tokens("info score wdl 100")), None);
+    }
+
+    #[test]
+    fn tablebase_hits()
+    {
+        assert_eq!(parse_tablebase_hits(&tokens("info depth 1 score cp 19998 nodes 20 tbhits 12 pv d2d3")), Some(12));
+        assert_eq!(parse_tablebase_hits(&tokens("info depth 12 score cp 35 nodes 1000")), None);
+        assert_eq!(parse_tablebase_hits(&tokens("info tbhits")), None);
+    }
+
+    #[test]
+    fn mates_beyond_any_centipawns()
+    {
+        assert!(EngineScore::MateIn(10).as_centipawns() > EngineScore::Centipawns(5000).as_centipawns());
+        assert!(EngineScore::MateIn(1).as_centipawns() > EngineScore::MateIn(5).as_centipawns());
+        assert!(EngineScore::MateIn(-1).as_centipawns() < EngineScore::MateIn(-5).as_centipawns());
+        assert_eq!(EngineScore::MateIn(3).negated(), EngineScore::MateIn(-3));
+    }
+
+    #[test]
+    fn draw_offers()
+    {
+        let policy = DrawOfferPolicy {
+            max_engine_advantage_cp: 30,
+            min_moves_played: 20,
+        };
+        assert!(policy.accepts(EngineScore::Centipawns(30), 20));
+        assert!(policy.accepts(EngineScore::Centipawns(-200), 40));
+        assert!(policy.accepts(EngineScore::MateIn(-4), 25));
+        assert!(! policy.accepts(EngineScore::Centipawns(31), 20));
+        assert!(! policy.accepts(EngineScore::Centipawns(0), 19));
+        assert!(! policy.accepts(EngineScore::MateIn(8), 50));
+    }
+
+    #[test]
+    fn only_the_latest_analysis_is_kept()
+    {
+        let search = |purpose: SearchPurpose, fen: &str| EngineRequest::Search {
+            purpose,
+            fen: fen.to_string(),
+            movetime_ms: 100,
+        };
+        let mut pending = vec![
+            search(SearchPurpose::Analyse, "first"),
+            search(SearchPurpose::PlayMove, "second"),
+            search(SearchPurpose::Analyse, "third"),
+            search(SearchPurpose::AnswerDrawOffer, "fourth"),
+        ];
+        drop_outdated_analyses(&mut pending);
+        let fens: Vec<&str> = pending.iter().filter_map(|request| match request {
+            EngineRequest::Search { fen, .. } => Some(fen.as_str()),
+            _ => None
+        }).collect();
+        assert_eq!(fens, vec!["second", "third", "fourth"]);
+    }
+
+    #[test]
+    fn cancelled_searches_are_dropped()
+    {
+        let search = |purpose: SearchPurpose, fen: &str| EngineRequest::Search {
+            purpose,
+            fen: fen.to_string(),
+            movetime_ms: 100,
+        };
+        let mut pending = vec![
+            search(SearchPurpose::PlayMove, "first"),
+            search(SearchPurpose::Analyse, "second"),
+            EngineRequest::Cancel { purpose: SearchPurpose::PlayMove },
+            search(SearchPurpose::PlayMove, "third"),
+        ];
+        apply_cancellations(&mut pending);
+        let fens: Vec<&str> = pending.iter().filter_map(|request| match request {
+            EngineRequest::Search { fen, .. } => Some(fen.as_str()),
+            _ => None
+        }).collect();
+        assert_eq!(fens, vec!["second", "third"]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WinReason
+{
+    Checkmate,
+    Resignation,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DrawReason
+{
+    Stalemate,
+    FiftyMoves,
+    Repetition,
+    Agreement,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameResult
+{
+    WhiteWins(WinReason),
+    BlackWins(WinReason),
+    Draw(DrawReason),
+}
+
+impl GameResult
+{
+    pub fn pgn_tag(&self) -> &'static str
+    {
+        match self {
+            GameResult::WhiteWins(_) => "1-0",
+            GameResult::BlackWins(_) => "0-1",
+            GameResult::Draw(_) => "1/2-1/2",
+        }
+    }
+
+    pub fn description(&self) -> String
+    {
+        match self {
+            GameResult::WhiteWins(reason) => format!("White wins ({}).", win_reason_text(*reason, "Black")),
+            GameResult::BlackWins(reason) => format!("Black wins ({}).", win_reason_text(*reason, "White")),
+            GameResult::Draw(reason) => format!("Draw ({}).", draw_reason_text(*reason)),
+        }
+    }
+}
+
+fn win_reason_text(reason: WinReason, loser: &str) -> String
+{
+    match reason {
+        WinReason::Checkmate => String::from("checkmate"),
+        WinReason::Resignation => format!("{} resigned", loser),
+    }
+}
+
+fn draw_reason_text(reason: DrawReason) -> &'static str
+{
+    match reason {
+        DrawReason::Stalemate => "stalemate",
+        DrawReason::FiftyMoves => "fifty-move rule",
+        DrawReason::Repetition => "threefold repetition",
+        DrawReason::Agreement => "agreement",
+    }
+}
\ No newline at end of file
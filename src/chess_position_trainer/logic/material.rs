@@ -0,0 +1,161 @@
+use pleco::core::{Piece, PieceType, Player};
+
+const COUNTED_TYPES: [PieceType; 5] = [PieceType::Q, PieceType::R, PieceType::B, PieceType::N, PieceType::P];
+
+pub fn piece_value(piece_type: PieceType) -> i32
+{
+    match piece_type {
+        PieceType::P => 1,
+        PieceType::N | PieceType::B => 3,
+        PieceType::R => 5,
+        PieceType::Q => 9,
+        _ => 0,
+    }
+}
+
+/// What each side captured since the start of the drill, and how the material
+/// balance moved since then, in pawns from the white point of view.
+/// As training positions rarely start with the full material, both are
+/// computed against the start position rather than the standard one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialBalance
+{
+    /// Black pieces captured by white, the most valuable first.
+    pub captured_by_white: Vec<PieceType>,
+    /// White pieces captured by black, the most valuable first.
+    pub captured_by_black: Vec<PieceType>,
+    pub balance: i32,
+}
+
+impl MaterialBalance
+{
+    /// Compares the pieces of both positions, given for each of the 64 cells.
+    pub fn between(start: &[Piece], current: &[Piece]) -> MaterialBalance
+    {
+        let material = |pieces: &[Piece], player: Player| -> i32 {
+            pieces.iter()
+                .filter(|piece| piece.player() == Some(player))
+                .map(|piece| piece_value(piece.type_of()))
+                .sum()
+        };
+        let material_difference = |pieces: &[Piece]| material(pieces, Player::White) - material(pieces, Player::Black);
+
+        MaterialBalance {
+            captured_by_white: lost_pieces(start, current, Player::Black),
+            captured_by_black: lost_pieces(start, current, Player::White),
+            balance: material_difference(current) - material_difference(start),
+        }
+    }
+
+    /// Such as "White captured : ♜♟ +4   Black captured : ♘", empty if nothing changed.
+    pub fn description(&self) -> String
+    {
+        let side_description = |side: &str, captured: &[PieceType], captured_white: bool, advantage: i32| {
+            let mut description = String::from(side);
+            if ! captured.is_empty() {
+                description.push_str(" captured : ");
+                captured.iter().for_each(|piece_type| description.push(piece_symbol(*piece_type, captured_white)));
+            }
+            if advantage > 0 {
+                description.push_str(&format!(" +{}", advantage));
+            }
+            description
+        };
+
+        let mut sides = vec![];
+        if ! self.captured_by_white.is_empty() || self.balance > 0 {
+            sides.push(side_description("White", &self.captured_by_white, false, self.balance));
+        }
+        if ! self.captured_by_black.is_empty() || self.balance < 0 {
+            sides.push(side_description("Black", &self.captured_by_black, true, -self.balance));
+        }
+        sides.join("   ")
+    }
+}
+
+/// The pieces of the player missing from the current position, the most valuable first.
+/// The pieces beyond the start ones come from promotions : as many pawns were promoted, not lost.
+fn lost_pieces(start: &[Piece], current: &[Piece], player: Player) -> Vec<PieceType>
+{
+    let count = |pieces: &[Piece], piece_type: PieceType| -> i32 {
+        pieces.iter()
+            .filter(|piece| piece.player() == Some(player) && piece.type_of() == piece_type)
+            .count() as i32
+    };
+    let missing: Vec<(PieceType, i32)> = COUNTED_TYPES.iter()
+        .map(|piece_type| (*piece_type, count(start, *piece_type) - count(current, *piece_type)))
+        .collect();
+    let promotions: i32 = missing.iter()
+        .filter(|(piece_type, _missing)| *piece_type != PieceType::P)
+        .map(|(_piece_type, missing)| (-missing).max(0))
+        .sum();
+
+    missing.iter().flat_map(|(piece_type, missing)| {
+        let lost = if *piece_type == PieceType::P { missing - promotions } else { *missing };
+        std::iter::repeat_n(*piece_type, lost.max(0) as usize)
+    }).collect()
+}
+
+fn piece_symbol(piece_type: PieceType, white: bool) -> char
+{
+    match (piece_type, white) {
+        (PieceType::Q, true) => '♕',
+        (PieceType::R, true) => '♖',
+        (PieceType::B, true) => '♗',
+        (PieceType::N, true) => '♘',
+        (_, true) => '♙',
+        (PieceType::Q, false) => '♛',
+        (PieceType::R, false) => '♜',
+        (PieceType::B, false) => '♝',
+        (PieceType::N, false) => '♞',
+        (_, false) => '♟',
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use pleco::{Board};
+    use pleco::core::sq::{SQ};
+
+    fn pieces(fen: &str) -> Vec<Piece>
+    {
+        let board = Board::from_fen(fen).expect("Bad test position !");
+        (0..64u8).map(|cell| board.piece_at_sq(SQ::from(cell))).collect()
+    }
+
+    #[test]
+    fn nothing_captured_from_the_start_position()
+    {
+        let start = pieces("4k3/pp6/8/8/8/8/5PPP/R3K3 w - - 0 1");
+        let balance = MaterialBalance::between(&start, &start);
+        assert_eq!(balance.captured_by_white, vec![]);
+        assert_eq!(balance.captured_by_black, vec![]);
+        assert_eq!(balance.balance, 0);
+        assert_eq!(balance.description(), "");
+    }
+
+    #[test]
+    fn counts_against_the_drill_start()
+    {
+        let start = pieces("r3k3/pp6/8/8/8/8/5PPP/R2QK3 w - - 0 1");
+        let current = pieces("4k3/1p6/8/8/8/8/6PP/R3K3 w - - 0 1");
+        let balance = MaterialBalance::between(&start, &current);
+        assert_eq!(balance.captured_by_white, vec![PieceType::R, PieceType::P]);
+        assert_eq!(balance.captured_by_black, vec![PieceType::Q, PieceType::P]);
+        assert_eq!(balance.balance, -4);
+        assert_eq!(balance.description(), "White captured : ♜♟   Black captured : ♕♙ +4");
+    }
+
+    #[test]
+    fn promoted_pawns_are_not_captured()
+    {
+        let start = pieces("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        let current = pieces("1Q2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        let balance = MaterialBalance::between(&start, &current);
+        assert_eq!(balance.captured_by_black, vec![]);
+        assert_eq!(balance.balance, 8);
+        assert_eq!(balance.description(), "White +8");
+    }
+}
\ No newline at end of file
@@ -1 +1,19 @@
-pub mod chessgame;
\ No newline at end of file
+pub mod chessgame;
+pub mod notation;
+pub mod move_outcome;
+pub mod game_result;
+pub mod pgn;
+pub mod engine;
+pub mod preferences;
+pub mod side_choice;
+pub mod variation_tree;
+pub mod annotation;
+pub mod material;
+pub mod blindfold;
+pub mod coordinates;
+pub mod training_goal;
+pub mod tablebase;
+pub mod spaced_repetition;
+pub mod position_library;
+#[cfg(test)]
+pub mod test_helpers;
\ No newline at end of file
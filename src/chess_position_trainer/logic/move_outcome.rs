@@ -0,0 +1,44 @@
+use std::fmt;
+use pleco::core::{PieceType};
+use pleco::core::sq::{SQ};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveOutcome
+{
+    pub start_cell: SQ,
+    pub end_cell: SQ,
+    pub promotion: PieceType,
+    pub captured: PieceType,
+    pub san: String,
+    pub uci: String,
+    pub gives_check: bool,
+    pub gives_mate: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveError
+{
+    Empty,
+    Malformed(String),
+    Illegal(String),
+    MissingPromotion(String),
+    Ambiguous(String, Vec<String>),
+    GameOver,
+    NotYourTurn,
+}
+
+impl fmt::Display for MoveError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            MoveError::Empty => write!(f, "Please type a move."),
+            MoveError::Malformed(text) => write!(f, "Could not understand the move {} !", text),
+            MoveError::Illegal(text) => write!(f, "The move {} is not legal here !", text),
+            MoveError::MissingPromotion(text) => write!(f, "The move {} needs a promotion piece (Q, R, B or N) !", text),
+            MoveError::Ambiguous(text, candidates) => write!(f, "The move {} is ambiguous : {} ?", text, candidates.join(", ")),
+            MoveError::GameOver => write!(f, "The game is over."),
+            MoveError::NotYourTurn => write!(f, "Please wait for the computer to play."),
+        }
+    }
+}
\ No newline at end of file
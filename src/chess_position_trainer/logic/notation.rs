@@ -0,0 +1,411 @@
+use pleco::{Board};
+use pleco::core::{PieceType};
+use pleco::core::sq::{SQ};
+use pleco::core::piece_move::{BitMove};
+use pleco::core::move_list::{MoveList};
+use super::move_outcome::{MoveError};
+
+struct SanPattern
+{
+    piece_type: PieceType,
+    start_file: Option<u8>,
+    start_rank: Option<u8>,
+    end_cell: SQ,
+    promotion: Option<PieceType>,
+}
+
+pub fn cell_from_coordinates(file: u8, rank: u8) -> SQ
+{
+    SQ::from(file + 8 * rank)
+}
+
+fn file_from_char(c: char) -> Option<u8>
+{
+    if ('a'..='h').contains(&c) { Some(c as u8 - b'a') } else { None }
+}
+
+fn rank_from_char(c: char) -> Option<u8>
+{
+    if ('1'..='8').contains(&c) { Some(c as u8 - b'1') } else { None }
+}
+
+fn piece_type_from_char(c: char) -> Option<PieceType>
+{
+    match c.to_ascii_uppercase() {
+        'N' => Some(PieceType::N),
+        'B' => Some(PieceType::B),
+        'R' => Some(PieceType::R),
+        'Q' => Some(PieceType::Q),
+        'K' => Some(PieceType::K),
+        _ => None
+    }
+}
+
+/// Gives the SAN of a legal move of the given position, including check and mate suffixes.
+pub fn move_to_san(position: &Board, the_move: BitMove) -> String
+{
+    let mut san = move_to_san_without_suffix(position, the_move);
+
+    let mut position_after = position.shallow_clone();
+    position_after.apply_move(the_move);
+    if position_after.checkmate() {
+        san.push('#');
+    }
+    else if position_after.in_check() {
+        san.push('+');
+    }
+
+    san
+}
+
+fn move_to_san_without_suffix(position: &Board, the_move: BitMove) -> String
+{
+    if the_move.is_king_castle() {
+        return String::from("O-O");
+    }
+    if the_move.is_queen_castle() {
+        return String::from("O-O-O");
+    }
+
+    let start_cell = the_move.get_src();
+    let end_cell = the_move.get_dest();
+    let piece_type = position.moved_piece(the_move).type_of();
+    let is_capture = the_move.is_capture();
+
+    let mut san = String::new();
+
+    if piece_type == PieceType::P {
+        if is_capture {
+            san.push_str(&start_cell.to_string()[0..1]);
+        }
+    }
+    else {
+        san.push(piece_type.char_upper());
+
+        let legal_moves_list: MoveList = position.generate_moves();
+        let rivals: Vec<&BitMove> = legal_moves_list.iter().filter(|m| {
+            m.get_dest() == end_cell &&
+                m.get_src() != start_cell &&
+                position.moved_piece(**m).type_of() == piece_type
+        }).collect();
+
+        if ! rivals.is_empty() {
+            let same_file = rivals.iter().any(|m| m.get_src().file_idx_of_sq() == start_cell.file_idx_of_sq());
+            let same_rank = rivals.iter().any(|m| m.get_src().rank_idx_of_sq() == start_cell.rank_idx_of_sq());
+            let start_cell_name = start_cell.to_string();
+
+            if ! same_file {
+                san.push_str(&start_cell_name[0..1]);
+            }
+            else if ! same_rank {
+                san.push_str(&start_cell_name[1..2]);
+            }
+            else {
+                san.push_str(&start_cell_name);
+            }
+        }
+    }
+
+    if is_capture {
+        san.push('x');
+    }
+    san.push_str(&end_cell.to_string());
+
+    if the_move.is_promo() {
+        san.push('=');
+        san.push(the_move.promo_piece().char_upper());
+    }
+
+    san
+}
+
+/// Converts a line of UCI moves played from the given position into SAN,
+/// stopping at the first move which is not legal.
+pub fn uci_line_to_san(position: &Board, uci_moves: &[String]) -> Vec<String>
+{
+    let mut position = position.shallow_clone();
+    let mut line = vec![];
+    for uci_move in uci_moves {
+        match parse_move(&position, uci_move) {
+            Ok(the_move) => {
+                line.push(move_to_san(&position, the_move));
+                position.apply_move(the_move);
+            },
+            Err(_) => break
+        }
+    }
+    line
+}
+
+/// Finds the legal move of the given position matching a move typed either
+/// in SAN (Nf3, exd8=Q+, O-O) or in UCI (g1f3, e7e8q).
+pub fn parse_move(position: &Board, text: &str) -> Result<BitMove, MoveError>
+{
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(MoveError::Empty);
+    }
+
+    let legal_moves_list: MoveList = position.generate_moves();
+    let legal_moves: Vec<BitMove> = legal_moves_list.iter().cloned().collect();
+
+    let candidates: Vec<BitMove> = if is_uci_move(text) {
+        matching_uci_moves(&legal_moves, text)
+    }
+    else {
+        let cleaned: String = text.chars()
+            .filter(|c| ! "+#!?".contains(*c))
+            .collect();
+
+        match cleaned.as_str() {
+            "O-O" | "0-0" => legal_moves.iter().filter(|m| m.is_king_castle()).cloned().collect(),
+            "O-O-O" | "0-0-0" => legal_moves.iter().filter(|m| m.is_queen_castle()).cloned().collect(),
+            _ => {
+                let pattern = parse_san_pattern(&cleaned)
+                    .ok_or_else(|| MoveError::Malformed(text.to_string()))?;
+                matching_san_moves(position, &legal_moves, &pattern)
+            }
+        }
+    };
+
+    match candidates.len() {
+        0 => Err(MoveError::Illegal(text.to_string())),
+        1 => Ok(candidates[0]),
+        _ => {
+            if candidates.iter().all(|m| m.is_promo()) {
+                let first = candidates[0];
+                let same_squares = candidates.iter().all(|m| {
+                    m.get_src() == first.get_src() && m.get_dest() == first.get_dest()
+                });
+                if same_squares {
+                    return Err(MoveError::MissingPromotion(text.to_string()));
+                }
+            }
+            let names = candidates.iter().map(|m| move_to_san(position, *m)).collect();
+            Err(MoveError::Ambiguous(text.to_string(), names))
+        }
+    }
+}
+
+fn is_uci_move(text: &str) -> bool
+{
+    let chars: Vec<char> = text.chars().collect();
+    (chars.len() == 4 || chars.len() == 5) &&
+        file_from_char(chars[0]).is_some() &&
+        rank_from_char(chars[1]).is_some() &&
+        file_from_char(chars[2]).is_some() &&
+        rank_from_char(chars[3]).is_some() &&
+        (chars.len() == 4 || "qrbn".contains(chars[4].to_ascii_lowercase()))
+}
+
+fn matching_uci_moves(legal_moves: &[BitMove], text: &str) -> Vec<BitMove>
+{
+    let text = text.to_ascii_lowercase();
+    let squares = &text[0..4];
+    let promotion = text.chars().nth(4);
+
+    legal_moves.iter().filter(|m| {
+        let raw_squares = format!("{}{}", m.get_src().to_string(), m.get_dest().to_string());
+        let uci_squares = &m.stringify()[0..4];
+        let squares_match = squares == uci_squares || squares == raw_squares;
+        let promotion_match = if m.is_promo() {
+            match promotion {
+                Some(promotion) => promotion == m.promo_piece().char_lower(),
+                None => true
+            }
+        } else { promotion.is_none() };
+
+        squares_match && promotion_match
+    }).cloned().collect()
+}
+
+fn parse_san_pattern(text: &str) -> Option<SanPattern>
+{
+    let mut chars: Vec<char> = text.chars().filter(|c| *c != 'x' && *c != ':').collect();
+
+    let mut piece_type = PieceType::P;
+    if let Some(first) = chars.first() {
+        if first.is_ascii_uppercase() {
+            piece_type = piece_type_from_char(*first)?;
+            chars.remove(0);
+        }
+    }
+
+    let mut promotion = None;
+    if let Some(last) = chars.last() {
+        if let Some(promotion_type) = piece_type_from_char(*last) {
+            if last.is_ascii_uppercase() || (*last != 'b' && piece_type == PieceType::P) {
+                if promotion_type == PieceType::K || piece_type != PieceType::P {
+                    return None;
+                }
+                promotion = Some(promotion_type);
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+        }
+    }
+
+    if chars.len() < 2 {
+        return None;
+    }
+    let end_rank = rank_from_char(chars.pop()?)?;
+    let end_file = file_from_char(chars.pop()?)?;
+
+    let mut start_file = None;
+    let mut start_rank = None;
+    for c in chars {
+        if let Some(file) = file_from_char(c) {
+            if start_file.is_some() || start_rank.is_some() { return None; }
+            start_file = Some(file);
+        }
+        else if let Some(rank) = rank_from_char(c) {
+            if start_rank.is_some() { return None; }
+            start_rank = Some(rank);
+        }
+        else {
+            return None;
+        }
+    }
+
+    Some(SanPattern {
+        piece_type,
+        start_file,
+        start_rank,
+        end_cell: cell_from_coordinates(end_file, end_rank),
+        promotion,
+    })
+}
+
+fn matching_san_moves(position: &Board, legal_moves: &[BitMove], pattern: &SanPattern) -> Vec<BitMove>
+{
+    legal_moves.iter().filter(|m| {
+        if m.is_castle() || m.get_dest() != pattern.end_cell {
+            return false;
+        }
+        if position.moved_piece(**m).type_of() != pattern.piece_type {
+            return false;
+        }
+        if let Some(file) = pattern.start_file {
+            if m.get_src().file_idx_of_sq() != file { return false; }
+        }
+        if let Some(rank) = pattern.start_rank {
+            if m.get_src().rank_idx_of_sq() != rank { return false; }
+        }
+        match pattern.promotion {
+            Some(promotion) => m.is_promo() && m.promo_piece() == promotion,
+            None => true
+        }
+    }).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const START_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    const CASTLES: &str = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    const PROMOTION: &str = "8/4P1k1/8/8/8/8/6K1/8 w - - 0 1";
+
+    fn board(fen: &str) -> Board
+    {
+        Board::from_fen(fen).expect("Bad test position !")
+    }
+
+    /// The UCI of the move typed in the position.
+    fn parse(fen: &str, text: &str) -> Result<String, MoveError>
+    {
+        parse_move(&board(fen), text).map(|the_move| the_move.stringify())
+    }
+
+    /// The SAN written back for the move typed in the position.
+    fn san(fen: &str, text: &str) -> String
+    {
+        let position = board(fen);
+        let the_move = parse_move(&position, text).expect("Bad test move !");
+        move_to_san(&position, the_move)
+    }
+
+    #[test]
+    fn san_and_uci()
+    {
+        assert_eq!(parse(START_POSITION, "Nf3"), Ok(String::from("g1f3")));
+        assert_eq!(parse(START_POSITION, "g1f3"), Ok(String::from("g1f3")));
+        assert_eq!(parse(START_POSITION, " e4 "), Ok(String::from("e2e4")));
+        assert_eq!(san(START_POSITION, "g1f3"), "Nf3");
+        assert_eq!(san(START_POSITION, "e2e4"), "e4");
+    }
+
+    #[test]
+    fn captures()
+    {
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2";
+        assert_eq!(parse(fen, "exd5"), Ok(String::from("e4d5")));
+        assert_eq!(parse(fen, "e:d5"), Ok(String::from("e4d5")));
+        assert_eq!(parse(fen, "ed5"), Ok(String::from("e4d5")));
+        assert_eq!(san(fen, "e4d5"), "exd5");
+        assert_eq!(san("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", "Rd5"), "Rxd5");
+    }
+
+    #[test]
+    fn castling()
+    {
+        assert_eq!(parse(CASTLES, "O-O"), parse(CASTLES, "e1g1"));
+        assert_eq!(parse(CASTLES, "0-0"), parse(CASTLES, "e1g1"));
+        assert_eq!(parse(CASTLES, "O-O-O"), parse(CASTLES, "e1c1"));
+        assert_eq!(san(CASTLES, "O-O"), "O-O");
+        assert_eq!(san(CASTLES, "e1c1"), "O-O-O");
+    }
+
+    #[test]
+    fn disambiguation()
+    {
+        let knights = "4k3/8/8/8/8/8/8/1N3N1K w - - 0 1";
+        assert_eq!(parse(knights, "Nd2"), Err(MoveError::Ambiguous(
+            String::from("Nd2"),
+            vec![String::from("Nbd2"), String::from("Nfd2")]
+        )));
+        assert_eq!(parse(knights, "Nbd2"), Ok(String::from("b1d2")));
+        assert_eq!(san(knights, "f1d2"), "Nfd2");
+
+        let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(parse(rooks, "R1a3"), Ok(String::from("a1a3")));
+        assert_eq!(san(rooks, "a5a3"), "R5a3");
+    }
+
+    #[test]
+    fn promotions()
+    {
+        assert_eq!(parse(PROMOTION, "e8"), Err(MoveError::MissingPromotion(String::from("e8"))));
+        assert_eq!(parse(PROMOTION, "e7e8"), Err(MoveError::MissingPromotion(String::from("e7e8"))));
+        assert_eq!(parse(PROMOTION, "e8=Q"), Ok(String::from("e7e8q")));
+        assert_eq!(parse(PROMOTION, "e8N"), Ok(String::from("e7e8n")));
+        assert_eq!(parse(PROMOTION, "e7e8r"), Ok(String::from("e7e8r")));
+        assert_eq!(san(PROMOTION, "e7e8q"), "e8=Q");
+        assert_eq!(san(PROMOTION, "e7e8n"), "e8=N+");
+    }
+
+    #[test]
+    fn bad_moves()
+    {
+        assert_eq!(parse(START_POSITION, ""), Err(MoveError::Empty));
+        assert_eq!(parse(START_POSITION, "hello"), Err(MoveError::Malformed(String::from("hello"))));
+        assert_eq!(parse(START_POSITION, "Nf6"), Err(MoveError::Illegal(String::from("Nf6"))));
+        assert_eq!(parse(START_POSITION, "e2e5"), Err(MoveError::Illegal(String::from("e2e5"))));
+        assert_eq!(parse(START_POSITION, "O-O"), Err(MoveError::Illegal(String::from("O-O"))));
+    }
+
+    #[test]
+    fn check_and_mate_suffixes()
+    {
+        assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8+");
+        assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+        assert_eq!(parse("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "Ra8#"), Ok(String::from("a1a8")));
+        assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "Ra8+!?"), "Ra8+");
+        assert_eq!(uci_line_to_san(&board(START_POSITION), &[
+            String::from("e2e4"), String::from("e7e5"), String::from("e1e3"), String::from("g1f3"),
+        ]), vec![String::from("e4"), String::from("e5")]);
+    }
+}
\ No newline at end of file
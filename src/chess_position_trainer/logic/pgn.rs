@@ -0,0 +1,123 @@
+const MAX_LINE_LENGTH: usize = 79;
+
+/// Writes a PGN game starting from the given FEN, with the moves given in SAN,
+/// each one followed by its comment (empty for no comment).
+/// The start comment is written before the first move.
+pub fn write_pgn(start_fen: &str, start_comment: &str, moves: &[(String, String)], result_tag: &str) -> String
+{
+    let fen_fields: Vec<&str> = start_fen.split_whitespace().collect();
+    let white_starts = fen_fields.get(1) != Some(&"b");
+    let first_move_number = fen_fields.get(5)
+        .and_then(|number| number.parse::<u32>().ok())
+        .unwrap_or(1);
+
+    let mut pgn = String::new();
+    let headers = [
+        ("Event", "Chess Position Trainer"),
+        ("Site", "?"),
+        ("Date", "????.??.??"),
+        ("Round", "-"),
+        ("White", "?"),
+        ("Black", "?"),
+        ("Result", result_tag),
+        ("SetUp", "1"),
+        ("FEN", start_fen),
+    ];
+    headers.iter().for_each(|(name, value)| {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, value));
+    });
+    pgn.push('\n');
+
+    let mut tokens: Vec<String> = vec![];
+    if ! start_comment.is_empty() {
+        tokens.push(format!("{{{}}}", start_comment));
+    }
+    let mut after_comment = false;
+    moves.iter().enumerate().for_each(|(index, (san, comment))| {
+        let ply = index as u32 + if white_starts { 0 } else { 1 };
+        let move_number = first_move_number + ply / 2;
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}.", move_number));
+        }
+        else if index == 0 || after_comment {
+            tokens.push(format!("{}...", move_number));
+        }
+        tokens.push(san.clone());
+
+        after_comment = ! comment.is_empty();
+        if after_comment {
+            tokens.push(format!("{{{}}}", comment));
+        }
+    });
+    tokens.push(String::from(result_tag));
+
+    let mut line_length = 0;
+    tokens.iter().for_each(|token| {
+        if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+            pgn.push('\n');
+            line_length = 0;
+        }
+        if line_length > 0 {
+            pgn.push(' ');
+            line_length += 1;
+        }
+        pgn.push_str(token);
+        line_length += token.len();
+    });
+    pgn.push('\n');
+
+    pgn
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn moves(sans: &[&str]) -> Vec<(String, String)>
+    {
+        sans.iter().map(|san| (san.to_string(), String::new())).collect()
+    }
+
+    #[test]
+    fn headers_and_result()
+    {
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let pgn = write_pgn(fen, "", &moves(&["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"]), "1-0");
+        assert!(pgn.starts_with("[Event \"Chess Position Trainer\"]\n"));
+        assert!(pgn.contains("[Result \"1-0\"]\n"));
+        assert!(pgn.contains(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n\n", fen)));
+        assert!(pgn.ends_with("\n1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n"));
+    }
+
+    #[test]
+    fn black_to_move_first()
+    {
+        let pgn = write_pgn("4k3/8/8/8/8/8/8/4K2R b K - 3 40", "", &moves(&["Kd7", "Rh7+"]), "*");
+        assert!(pgn.ends_with("\n40... Kd7 41. Rh7+ *\n"));
+    }
+
+    #[test]
+    fn comments()
+    {
+        let moves = vec![
+            (String::from("e4"), String::from("[%csl Ge4]")),
+            (String::from("e5"), String::new()),
+            (String::from("Nf3"), String::new()),
+        ];
+        let pgn = write_pgn("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "start", &moves, "*");
+        assert!(pgn.ends_with("\n{start} 1. e4 {[%csl Ge4]} 1... e5 2. Nf3 *\n"));
+    }
+
+    #[test]
+    fn long_lines_are_wrapped()
+    {
+        let sans: Vec<&str> = ["Nf3", "Nf6", "Ng1", "Ng8"].iter().cycle().take(60).cloned().collect();
+        let pgn = write_pgn("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "", &moves(&sans), "1/2-1/2");
+        let move_lines: Vec<&str> = pgn.split("\n\n").nth(1).unwrap().lines().collect();
+        assert!(move_lines.len() > 1);
+        assert!(move_lines.iter().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert_eq!(move_lines.join(" ").split_whitespace().filter(|token| token.ends_with('.')).count(), 30);
+        assert!(pgn.ends_with(" 1/2-1/2\n"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::PathBuf;
+use super::preferences::application_folder;
+use super::side_choice::SideChoice;
+use super::training_goal::TrainingGoal;
+use super::spaced_repetition::{ReviewSchedule, Grade};
+
+const LIBRARY_FILE: &str = "positions.txt";
+
+/// A position to train on, with what the user must achieve from it and when to review it again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrainingPosition
+{
+    pub fen: String,
+    pub side_choice: SideChoice,
+    pub goal: TrainingGoal,
+    pub schedule: ReviewSchedule,
+}
+
+/// The training positions, saved in the user configuration folder as lines of tab separated fields :
+/// FEN, side, goal, ease, interval in days, successful reviews in a row and due day.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PositionLibrary
+{
+    pub positions: Vec<TrainingPosition>,
+}
+
+impl PositionLibrary
+{
+    pub fn load() -> PositionLibrary
+    {
+        let content = library_file_path()
+            .and_then(|path| fs::read_to_string(path).ok());
+        match content {
+            Some(content) => PositionLibrary::from_text(&content),
+            None => PositionLibrary::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String>
+    {
+        let path = library_file_path().ok_or("Could not find the configuration folder !")?;
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)
+                .map_err(|e| format!("Could not create the folder {} ({})", folder.display(), e))?;
+        }
+        fs::write(&path, self.to_text())
+            .map_err(|e| format!("Could not save the positions into {} ({})", path.display(), e))
+    }
+
+    /// Adds the position, due at once, or changes the side and the goal of the
+    /// position with the same FEN, keeping its schedule.
+    /// Returns false if the position was already in the library.
+    pub fn add(&mut self, fen: &str, side_choice: SideChoice, goal: TrainingGoal, today: u64) -> bool
+    {
+        match self.positions.iter_mut().find(|position| position.fen == fen) {
+            Some(position) => {
+                position.side_choice = side_choice;
+                position.goal = goal;
+                false
+            },
+            None => {
+                self.positions.push(TrainingPosition {
+                    fen: fen.to_string(),
+                    side_choice,
+                    goal,
+                    schedule: ReviewSchedule::new(today),
+                });
+                true
+            }
+        }
+    }
+
+    /// The indexes of the positions to review, the longest overdue first.
+    pub fn due_positions(&self, today: u64) -> Vec<usize>
+    {
+        let mut due: Vec<usize> = (0..self.positions.len())
+            .filter(|index| self.positions[*index].schedule.is_due(today))
+            .collect();
+        due.sort_by_key(|index| self.positions[*index].schedule.due_day);
+        due
+    }
+
+    pub fn grade(&mut self, index: usize, grade: Grade, today: u64)
+    {
+        if let Some(position) = self.positions.get_mut(index) {
+            position.schedule = position.schedule.reviewed(grade, today);
+        }
+    }
+
+    /// The lines which cannot be read are skipped.
+    fn from_text(content: &str) -> PositionLibrary
+    {
+        let positions = content.lines().filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').map(|field| field.trim()).collect();
+            if fields.len() != 7 {
+                return None;
+            }
+            Some(TrainingPosition {
+                fen: fields[0].to_string(),
+                side_choice: SideChoice::from_key(fields[1])?,
+                goal: TrainingGoal::from_key(fields[2])?,
+                schedule: ReviewSchedule {
+                    ease: fields[3].parse().ok()?,
+                    interval_days: fields[4].parse().ok()?,
+                    repetitions: fields[5].parse().ok()?,
+                    due_day: fields[6].parse().ok()?,
+                },
+            })
+        }).collect();
+        PositionLibrary { positions }
+    }
+
+    fn to_text(&self) -> String
+    {
+        self.positions.iter()
+            .map(|position| format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                position.fen,
+                position.side_choice.key(),
+                position.goal.key(),
+                position.schedule.ease,
+                position.schedule.interval_days,
+                position.schedule.repetitions,
+                position.schedule.due_day,
+            ))
+            .collect()
+    }
+}
+
+fn library_file_path() -> Option<PathBuf>
+{
+    application_folder().map(|folder| folder.join(LIBRARY_FILE))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const ROOK_ENDING: &str = "4k3/8/8/8/8/8/8/4K2R w K - 0 1";
+    const PAWN_ENDING: &str = "8/8/8/4k3/8/8/4P3/4K3 w - - 0 1";
+
+    #[test]
+    fn saved_as_text()
+    {
+        let mut library = PositionLibrary::default();
+        library.add(ROOK_ENDING, SideChoice::White, TrainingGoal::MateIn(16), 100);
+        library.add(PAWN_ENDING, SideChoice::SideToMove, TrainingGoal::Promote, 100);
+        library.grade(0, Grade::Easy, 100);
+
+        let text = library.to_text();
+        assert_eq!(text.lines().next(), Some("4k3/8/8/8/8/8/8/4K2R w K - 0 1\twhite\tmate_in 16\t2.6\t1\t1\t101"));
+        assert_eq!(PositionLibrary::from_text(&text), library);
+        assert_eq!(PositionLibrary::from_text("a line\nanother\tone").positions, vec![]);
+    }
+
+    #[test]
+    fn adding_again_keeps_the_schedule()
+    {
+        let mut library = PositionLibrary::default();
+        assert!(library.add(ROOK_ENDING, SideChoice::White, TrainingGoal::Win, 100));
+        library.grade(0, Grade::Good, 100);
+        assert!(! library.add(ROOK_ENDING, SideChoice::Black, TrainingGoal::Draw, 100));
+        assert_eq!(library.positions.len(), 1);
+        assert_eq!(library.positions[0].goal, TrainingGoal::Draw);
+        assert_eq!(library.positions[0].schedule.due_day, 101);
+    }
+
+    #[test]
+    fn longest_overdue_first()
+    {
+        let mut library = PositionLibrary::default();
+        library.add(ROOK_ENDING, SideChoice::White, TrainingGoal::Win, 100);
+        library.add(PAWN_ENDING, SideChoice::White, TrainingGoal::Promote, 90);
+        library.add("4k3/8/8/8/8/8/8/3QK3 w - - 0 1", SideChoice::White, TrainingGoal::Win, 95);
+        library.grade(1, Grade::Good, 95);
+        assert_eq!(library.due_positions(95), vec![2]);
+        assert_eq!(library.due_positions(100), vec![2, 1, 0]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,197 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use super::engine::{DrawOfferPolicy};
+use super::blindfold::Blindfold;
+use super::coordinates::{CoordinatesOptions, CoordinatesPlacement};
+
+const APPLICATION_FOLDER: &str = "chess_position_trainer";
+const PREFERENCES_FILE: &str = "preferences.txt";
+pub const DEFAULT_THEME: &str = "Classic";
+pub const DEFAULT_PIECE_SET: &str = "Default";
+
+/// User preferences, saved as "key = value" lines in the user configuration folder.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preferences
+{
+    pub engine_path: String,
+    pub engine_movetime_ms: u32,
+    pub draw_offer_policy: DrawOfferPolicy,
+    /// The folder of the Syzygy tablebases files given to the engine, empty if there is none.
+    pub syzygy_path: String,
+    pub theme: String,
+    pub piece_set: String,
+    pub auto_queen: bool,
+    pub blindfold: Blindfold,
+    pub fade_delay_seconds: u32,
+    pub coordinates: CoordinatesOptions,
+}
+
+impl Default for Preferences
+{
+    fn default() -> Preferences
+    {
+        Preferences {
+            engine_path: String::from("stockfish"),
+            engine_movetime_ms: 1000,
+            draw_offer_policy: DrawOfferPolicy::default(),
+            syzygy_path: String::new(),
+            theme: String::from(DEFAULT_THEME),
+            piece_set: String::from(DEFAULT_PIECE_SET),
+            auto_queen: false,
+            blindfold: Blindfold::Off,
+            fade_delay_seconds: 5,
+            coordinates: CoordinatesOptions::default(),
+        }
+    }
+}
+
+impl Preferences
+{
+    pub fn load() -> Preferences
+    {
+        let content = preferences_file_path()
+            .and_then(|path| fs::read_to_string(path).ok());
+        match content {
+            Some(content) => Preferences::from_text(&content),
+            None => Preferences::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String>
+    {
+        let path = preferences_file_path().ok_or("Could not find the configuration folder !")?;
+        if let Some(folder) = path.parent() {
+            fs::create_dir_all(folder)
+                .map_err(|e| format!("Could not create the folder {} ({})", folder.display(), e))?;
+        }
+        fs::write(&path, self.to_text())
+            .map_err(|e| format!("Could not save the preferences into {} ({})", path.display(), e))
+    }
+
+    fn from_text(content: &str) -> Preferences
+    {
+        let mut preferences = Preferences::default();
+
+        content.lines().for_each(|line| {
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().unwrap_or("").trim();
+
+            match key {
+                "engine_path" => preferences.engine_path = value.to_string(),
+                "engine_movetime_ms" => if let Ok(value) = value.parse() {
+                    preferences.engine_movetime_ms = value;
+                },
+                "draw_offer_max_engine_advantage_cp" => if let Ok(value) = value.parse() {
+                    preferences.draw_offer_policy.max_engine_advantage_cp = value;
+                },
+                "draw_offer_min_moves_played" => if let Ok(value) = value.parse() {
+                    preferences.draw_offer_policy.min_moves_played = value;
+                },
+                "syzygy_path" => preferences.syzygy_path = value.to_string(),
+                "theme" => preferences.theme = value.to_string(),
+                "piece_set" => preferences.piece_set = value.to_string(),
+                "auto_queen" => if let Ok(value) = value.parse() {
+                    preferences.auto_queen = value;
+                },
+                "blindfold" => if let Some(value) = Blindfold::from_key(value) {
+                    preferences.blindfold = value;
+                },
+                "fade_delay_seconds" => if let Ok(value) = value.parse() {
+                    preferences.fade_delay_seconds = value;
+                },
+                "coordinates" => if let Some(value) = CoordinatesPlacement::from_key(value) {
+                    preferences.coordinates.placement = value;
+                },
+                "coordinates_uppercase" => if let Ok(value) = value.parse() {
+                    preferences.coordinates.uppercase = value;
+                },
+                "coordinates_cells_colours" => if let Ok(value) = value.parse() {
+                    preferences.coordinates.cells_colours = value;
+                },
+                _ => {}
+            }
+        });
+
+        preferences
+    }
+
+    fn to_text(&self) -> String
+    {
+        let entries = [
+            ("engine_path", self.engine_path.clone()),
+            ("engine_movetime_ms", self.engine_movetime_ms.to_string()),
+            ("draw_offer_max_engine_advantage_cp", self.draw_offer_policy.max_engine_advantage_cp.to_string()),
+            ("draw_offer_min_moves_played", self.draw_offer_policy.min_moves_played.to_string()),
+            ("syzygy_path", self.syzygy_path.clone()),
+            ("theme", self.theme.clone()),
+            ("piece_set", self.piece_set.clone()),
+            ("auto_queen", self.auto_queen.to_string()),
+            ("blindfold", self.blindfold.key().to_string()),
+            ("fade_delay_seconds", self.fade_delay_seconds.to_string()),
+            ("coordinates", self.coordinates.placement.key().to_string()),
+            ("coordinates_uppercase", self.coordinates.uppercase.to_string()),
+            ("coordinates_cells_colours", self.coordinates.cells_colours.to_string()),
+        ];
+
+        entries.iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value))
+            .collect()
+    }
+}
+
+/// The folder of the application inside the user configuration folder.
+pub fn application_folder() -> Option<PathBuf>
+{
+    let config_folder = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_folder.join(APPLICATION_FOLDER))
+}
+
+fn preferences_file_path() -> Option<PathBuf>
+{
+    application_folder().map(|folder| folder.join(PREFERENCES_FILE))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn saved_preferences_are_read_back()
+    {
+        let mut preferences = Preferences::default();
+        assert_eq!(Preferences::from_text(&preferences.to_text()), preferences);
+
+        preferences.engine_path = String::from("/opt/engines/my engine");
+        preferences.engine_movetime_ms = 2500;
+        preferences.draw_offer_policy = DrawOfferPolicy {
+            max_engine_advantage_cp: -50,
+            min_moves_played: 30,
+        };
+        preferences.syzygy_path = String::from("/opt/syzygy/3-4-5");
+        preferences.theme = String::from("Ocean");
+        preferences.auto_queen = true;
+        preferences.blindfold = Blindfold::Fade;
+        preferences.fade_delay_seconds = 12;
+        preferences.coordinates.placement = CoordinatesPlacement::InsideCorners;
+        preferences.coordinates.uppercase = false;
+        assert_eq!(Preferences::from_text(&preferences.to_text()), preferences);
+    }
+
+    #[test]
+    fn unknown_and_bad_values_are_skipped()
+    {
+        let preferences = Preferences::from_text(
+            "engine_movetime_ms = fast\nunknown = 3\nauto_queen = true\nblindfold = sometimes\nno equal sign\n"
+        );
+        assert_eq!(preferences, Preferences {
+            auto_queen: true,
+            ..Preferences::default()
+        });
+    }
+}
\ No newline at end of file
@@ -0,0 +1,64 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use pleco::core::{Player};
+
+/// Which side the user plays, the computer playing the other one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SideChoice
+{
+    White,
+    Black,
+    SideToMove,
+    Random,
+}
+
+pub const ALL_SIDE_CHOICES: [SideChoice; 4] = [
+    SideChoice::SideToMove,
+    SideChoice::White,
+    SideChoice::Black,
+    SideChoice::Random,
+];
+
+impl SideChoice
+{
+    pub fn label(&self) -> &'static str
+    {
+        match self {
+            SideChoice::White => "White",
+            SideChoice::Black => "Black",
+            SideChoice::SideToMove => "Side to move",
+            SideChoice::Random => "Random",
+        }
+    }
+
+    /// The name saved in the position library.
+    pub fn key(&self) -> &'static str
+    {
+        match self {
+            SideChoice::White => "white",
+            SideChoice::Black => "black",
+            SideChoice::SideToMove => "side_to_move",
+            SideChoice::Random => "random",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<SideChoice>
+    {
+        ALL_SIDE_CHOICES.iter().cloned().find(|choice| choice.key() == key)
+    }
+
+    pub fn resolve(&self, white_to_move: bool) -> Player
+    {
+        let white = match self {
+            SideChoice::White => true,
+            SideChoice::Black => false,
+            SideChoice::SideToMove => white_to_move,
+            SideChoice::Random => {
+                let nanos = SystemTime::now().duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.subsec_nanos())
+                    .unwrap_or(0);
+                nanos.is_multiple_of(2)
+            },
+        };
+        if white { Player::White } else { Player::Black }
+    }
+}
\ No newline at end of file
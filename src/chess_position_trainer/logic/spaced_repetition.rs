@@ -0,0 +1,152 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const INITIAL_EASE: f64 = 2.5;
+const MINIMUM_EASE: f64 = 1.3;
+
+/// How the review of a position went : a failed goal is always graded Again,
+/// the user grading the difficulty of the achieved ones.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Grade
+{
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+pub const SUCCESS_GRADES: [Grade; 3] = [Grade::Hard, Grade::Good, Grade::Easy];
+
+impl Grade
+{
+    pub fn label(&self) -> &'static str
+    {
+        match self {
+            Grade::Again => "Again",
+            Grade::Hard => "Hard",
+            Grade::Good => "Good",
+            Grade::Easy => "Easy",
+        }
+    }
+
+    /// The SM-2 response quality, from 0 to 5, below 3 meaning a failure.
+    fn quality(&self) -> u32
+    {
+        match self {
+            Grade::Again => 1,
+            Grade::Hard => 3,
+            Grade::Good => 4,
+            Grade::Easy => 5,
+        }
+    }
+}
+
+/// When a position must be reviewed again, following the SM-2 algorithm.
+/// The days are counted since the UNIX epoch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReviewSchedule
+{
+    pub ease: f64,
+    pub interval_days: u32,
+    /// The successful reviews in a row.
+    pub repetitions: u32,
+    pub due_day: u64,
+}
+
+impl ReviewSchedule
+{
+    /// A new position, due at once.
+    pub fn new(today: u64) -> ReviewSchedule
+    {
+        ReviewSchedule {
+            ease: INITIAL_EASE,
+            interval_days: 0,
+            repetitions: 0,
+            due_day: today,
+        }
+    }
+
+    pub fn is_due(&self, today: u64) -> bool
+    {
+        self.due_day <= today
+    }
+
+    /// The schedule after a review graded today.
+    pub fn reviewed(&self, grade: Grade, today: u64) -> ReviewSchedule
+    {
+        let quality = grade.quality();
+        let (interval_days, repetitions) = if quality < 3 {
+            (1, 0)
+        }
+        else {
+            let interval_days = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval_days as f64 * self.ease).round() as u32
+            };
+            (interval_days, self.repetitions + 1)
+        };
+        let missing_quality = (5 - quality) as f64;
+        let ease = (self.ease + 0.1 - missing_quality * (0.08 + missing_quality * 0.02)).max(MINIMUM_EASE);
+
+        ReviewSchedule {
+            ease,
+            interval_days,
+            repetitions,
+            due_day: today + interval_days as u64,
+        }
+    }
+}
+
+pub fn today() -> u64
+{
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn intervals_grow_with_the_successes()
+    {
+        let new = ReviewSchedule::new(100);
+        assert!(new.is_due(100));
+
+        let first = new.reviewed(Grade::Good, 100);
+        assert_eq!((first.interval_days, first.repetitions, first.due_day), (1, 1, 101));
+        assert!(! first.is_due(100));
+        let second = first.reviewed(Grade::Good, 101);
+        assert_eq!((second.interval_days, second.due_day), (6, 107));
+        let third = second.reviewed(Grade::Good, 107);
+        assert_eq!((third.interval_days, third.due_day), (15, 122));
+        assert!((third.ease - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ease_follows_the_grades()
+    {
+        let new = ReviewSchedule::new(0);
+        assert!((new.reviewed(Grade::Easy, 0).ease - 2.6).abs() < 1e-9);
+        assert!((new.reviewed(Grade::Hard, 0).ease - 2.36).abs() < 1e-9);
+        assert!((new.reviewed(Grade::Again, 0).ease - 1.96).abs() < 1e-9);
+
+        let mut schedule = new;
+        (0..10).for_each(|_| schedule = schedule.reviewed(Grade::Again, 0));
+        assert!((schedule.ease - MINIMUM_EASE).abs() < 1e-9);
+    }
+
+    #[test]
+    fn failure_starts_again()
+    {
+        let learnt = ReviewSchedule::new(0)
+            .reviewed(Grade::Good, 0)
+            .reviewed(Grade::Good, 1)
+            .reviewed(Grade::Easy, 7);
+        let failed = learnt.reviewed(Grade::Again, 30);
+        assert_eq!((failed.interval_days, failed.repetitions, failed.due_day), (1, 0, 31));
+    }
+}
\ No newline at end of file
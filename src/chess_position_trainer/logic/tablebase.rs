@@ -0,0 +1,159 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use pleco::{Board};
+use pleco::core::{Piece};
+use super::chessgame::board_pieces;
+use super::engine::EngineScore;
+
+/// The Syzygy tablebases go up to seven pieces, kings included.
+pub const MAX_TABLEBASE_PIECES: usize = 7;
+/// Engines report a tablebase win far beyond any material advantage :
+/// Stockfish gives 20000 centipawns, minus the moves needed to enter the tablebase.
+const TABLEBASE_WIN_CP: i32 = 10_000;
+
+/// What the tablebases tell of a position, for the side to move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TablebaseAnswer
+{
+    Win,
+    Draw,
+    Loss,
+    /// The position is in none of the tablebases files available.
+    NotInTables,
+}
+
+impl TablebaseAnswer
+{
+    /// Reads the answer from the search of an engine given the tablebases folder.
+    /// Only a search which hit the tablebases tells something.
+    pub fn from_engine_search(score: Option<EngineScore>, tablebase_hits: u64) -> TablebaseAnswer
+    {
+        if tablebase_hits == 0 {
+            return TablebaseAnswer::NotInTables;
+        }
+        match score {
+            Some(EngineScore::MateIn(moves)) if moves > 0 => TablebaseAnswer::Win,
+            Some(EngineScore::MateIn(_)) => TablebaseAnswer::Loss,
+            Some(EngineScore::Centipawns(value)) if value >= TABLEBASE_WIN_CP => TablebaseAnswer::Win,
+            Some(EngineScore::Centipawns(value)) if value <= -TABLEBASE_WIN_CP => TablebaseAnswer::Loss,
+            Some(EngineScore::Centipawns(_)) => TablebaseAnswer::Draw,
+            None => TablebaseAnswer::NotInTables
+        }
+    }
+}
+
+/// Gives what the tablebases tell of the position of the given FEN,
+/// or None while the answer is not known yet.
+pub trait TablebaseProbe
+{
+    fn probe(&self, fen: &str) -> Option<TablebaseAnswer>;
+}
+
+/// Probes the position, unless it has too many pieces to be in any tablebase.
+pub fn probe_board(tablebase: &dyn TablebaseProbe, board: &Board, fen: &str) -> Option<TablebaseAnswer>
+{
+    let pieces_count = board_pieces(board).iter().filter(|piece| **piece != Piece::None).count();
+    if pieces_count > MAX_TABLEBASE_PIECES {
+        Some(TablebaseAnswer::NotInTables)
+    }
+    else {
+        tablebase.probe(fen)
+    }
+}
+
+/// The answers received so far, by FEN.
+/// The positions probed before their answer came are noted, so that they are asked once.
+#[derive(Debug, Default)]
+pub struct TablebaseCache
+{
+    answers: HashMap<String, TablebaseAnswer>,
+    unknown: RefCell<Vec<String>>,
+    asked: HashSet<String>,
+}
+
+impl TablebaseCache
+{
+    pub fn insert(&mut self, fen: &str, answer: TablebaseAnswer)
+    {
+        self.answers.insert(fen.to_string(), answer);
+    }
+
+    /// The positions probed without answer and not asked yet, which are now considered asked.
+    pub fn take_questions(&mut self) -> Vec<String>
+    {
+        let unknown: Vec<String> = self.unknown.borrow_mut().drain(..).collect();
+        unknown.into_iter()
+            .filter(|fen| self.asked.insert(fen.clone()))
+            .collect()
+    }
+
+    /// Forgets the answers and the questions, for instance when the tablebases change.
+    pub fn clear(&mut self)
+    {
+        self.answers.clear();
+        self.unknown.borrow_mut().clear();
+        self.asked.clear();
+    }
+}
+
+impl TablebaseProbe for TablebaseCache
+{
+    fn probe(&self, fen: &str) -> Option<TablebaseAnswer>
+    {
+        let answer = self.answers.get(fen).cloned();
+        if answer.is_none() {
+            self.unknown.borrow_mut().push(fen.to_string());
+        }
+        answer
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn engine_searches()
+    {
+        let answer = TablebaseAnswer::from_engine_search;
+        assert_eq!(answer(Some(EngineScore::Centipawns(19985)), 12), TablebaseAnswer::Win);
+        assert_eq!(answer(Some(EngineScore::Centipawns(-19990)), 3), TablebaseAnswer::Loss);
+        assert_eq!(answer(Some(EngineScore::MateIn(7)), 1), TablebaseAnswer::Win);
+        assert_eq!(answer(Some(EngineScore::MateIn(-4)), 1), TablebaseAnswer::Loss);
+        assert_eq!(answer(Some(EngineScore::Centipawns(0)), 20), TablebaseAnswer::Draw);
+        // Without any tablebase hit, even a winning score tells nothing.
+        assert_eq!(answer(Some(EngineScore::Centipawns(19985)), 0), TablebaseAnswer::NotInTables);
+        assert_eq!(answer(None, 5), TablebaseAnswer::NotInTables);
+    }
+
+    #[test]
+    fn unknown_positions_are_asked_once()
+    {
+        let won = "4k3/8/8/8/8/8/8/4K2R b - - 0 1";
+        let drawn = "4k3/8/8/8/8/8/8/4K3 b - - 0 1";
+        let mut tablebase = TablebaseCache::default();
+        assert_eq!(tablebase.probe(won), None);
+        assert_eq!(tablebase.probe(won), None);
+        assert_eq!(tablebase.probe(drawn), None);
+        assert_eq!(tablebase.take_questions(), vec![won.to_string(), drawn.to_string()]);
+        assert_eq!(tablebase.probe(won), None);
+        assert!(tablebase.take_questions().is_empty());
+
+        tablebase.insert(won, TablebaseAnswer::Loss);
+        assert_eq!(tablebase.probe(won), Some(TablebaseAnswer::Loss));
+        tablebase.clear();
+        assert_eq!(tablebase.probe(won), None);
+        assert_eq!(tablebase.take_questions(), vec![won.to_string()]);
+    }
+
+    #[test]
+    fn crowded_positions_are_not_probed()
+    {
+        let tablebase = TablebaseCache::default();
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let board = Board::from_fen(start).expect("Bad test position !");
+        assert_eq!(probe_board(&tablebase, &board, start), Some(TablebaseAnswer::NotInTables));
+        assert!(tablebase.unknown.borrow().is_empty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,19 @@
+use pleco::core::sq::{SQ};
+use super::chessgame::ChessGame;
+
+/// The cell of the given name, such as "e4".
+pub fn cell(name: &str) -> SQ
+{
+    let bytes = name.as_bytes();
+    SQ::from((bytes[0] - b'a') + 8 * (bytes[1] - b'1'))
+}
+
+/// The game from the given position, after the given SAN moves.
+pub fn game(fen: &str, moves: &[&str]) -> ChessGame
+{
+    let mut game = ChessGame::new_from_fen(fen).expect("Bad test position !");
+    moves.iter().for_each(|move_text| {
+        game.do_typed_move(move_text).expect("Bad test move !");
+    });
+    game
+}
\ No newline at end of file
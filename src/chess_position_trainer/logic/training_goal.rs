@@ -0,0 +1,339 @@
+use pleco::{Board};
+use pleco::core::{Piece, PieceType, Player};
+use pleco::core::sq::{SQ};
+use super::chessgame::{ChessGame, board_pieces};
+use super::game_result::GameResult;
+use super::material::MaterialBalance;
+use super::tablebase::{TablebaseAnswer, TablebaseProbe, probe_board};
+
+/// What the user must achieve from a training position.
+/// The numbers of moves count the moves of the user only.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrainingGoal
+{
+    Win,
+    /// Holding the draw, winning being fine too.
+    Draw,
+    MateIn(u32),
+    Promote,
+    /// Being ahead of the given number of pawns, once the computer has answered.
+    WinMaterial(u32),
+    /// Reaching, within the given number of moves, a position the Syzygy tablebases report won.
+    TablebaseWin(u32),
+}
+
+pub const ALL_TRAINING_GOALS: [TrainingGoal; 6] = [
+    TrainingGoal::Win,
+    TrainingGoal::Draw,
+    TrainingGoal::MateIn(3),
+    TrainingGoal::Promote,
+    TrainingGoal::WinMaterial(3),
+    TrainingGoal::TablebaseWin(10),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GoalStatus
+{
+    Pending,
+    Achieved,
+    Failed,
+}
+
+impl TrainingGoal
+{
+    /// The name of the goal, without its number.
+    pub fn kind_label(&self) -> &'static str
+    {
+        match self {
+            TrainingGoal::Win => "Win",
+            TrainingGoal::Draw => "Draw",
+            TrainingGoal::MateIn(_) => "Mate in N moves",
+            TrainingGoal::Promote => "Promote a pawn",
+            TrainingGoal::WinMaterial(_) => "Win N pawns of material",
+            TrainingGoal::TablebaseWin(_) => "Reach a tablebase win in N moves",
+        }
+    }
+
+    pub fn description(&self) -> String
+    {
+        match self {
+            TrainingGoal::Win => String::from("win"),
+            TrainingGoal::Draw => String::from("draw"),
+            TrainingGoal::MateIn(moves) => format!("mate in {}", moves),
+            TrainingGoal::Promote => String::from("promote a pawn"),
+            TrainingGoal::WinMaterial(pawns) => format!("win {} pawns of material", pawns),
+            TrainingGoal::TablebaseWin(moves) => format!("reach a tablebase win in {} moves", moves),
+        }
+    }
+
+    /// The name saved in the position library, such as "mate_in 3".
+    pub fn key(&self) -> String
+    {
+        let name = match self {
+            TrainingGoal::Win => "win",
+            TrainingGoal::Draw => "draw",
+            TrainingGoal::MateIn(_) => "mate_in",
+            TrainingGoal::Promote => "promote",
+            TrainingGoal::WinMaterial(_) => "win_material",
+            TrainingGoal::TablebaseWin(_) => "tablebase_win",
+        };
+        match self.count() {
+            Some(count) => format!("{} {}", name, count),
+            None => String::from(name)
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<TrainingGoal>
+    {
+        let mut parts = key.split_whitespace();
+        let name = parts.next()?;
+        let goal = ALL_TRAINING_GOALS.iter().cloned()
+            .find(|goal| goal.key().split_whitespace().next() == Some(name))?;
+        match (goal.count(), parts.next()) {
+            (Some(_), Some(count)) => count.parse().ok().map(|count| goal.with_count(count)),
+            (None, None) => Some(goal),
+            _ => None
+        }
+    }
+
+    /// The number of moves or pawns of the goal, if it has one.
+    pub fn count(&self) -> Option<u32>
+    {
+        match self {
+            TrainingGoal::MateIn(count) | TrainingGoal::WinMaterial(count) | TrainingGoal::TablebaseWin(count) =>
+                Some(*count),
+            _ => None
+        }
+    }
+
+    /// The same goal with another number, goals without number being kept as they are.
+    pub fn with_count(&self, count: u32) -> TrainingGoal
+    {
+        let count = count.max(1);
+        match self {
+            TrainingGoal::MateIn(_) => TrainingGoal::MateIn(count),
+            TrainingGoal::WinMaterial(_) => TrainingGoal::WinMaterial(count),
+            TrainingGoal::TablebaseWin(_) => TrainingGoal::TablebaseWin(count),
+            _ => *self
+        }
+    }
+
+    /// Replays the moves from the start position of the game, the user playing the given side.
+    /// The first move deciding the goal gives the status, then the end of the game if any.
+    /// The goal stays pending while a position waits for its tablebase answer.
+    pub fn evaluate(&self, game: &ChessGame, user_side: Player, tablebase: &dyn TablebaseProbe) -> GoalStatus
+    {
+        let tree = game.get_variation_tree();
+        let start_pieces = match Board::from_fen(game.get_start_fen()) {
+            Ok(board) => board_pieces(&board),
+            Err(_) => return GoalStatus::Pending
+        };
+
+        let mut user_moves = 0;
+        for node in tree.path_to(game.get_current_node()) {
+            let fen = tree.get_fen(Some(node));
+            let board = match Board::from_fen(fen) {
+                Ok(board) => board,
+                Err(_) => return GoalStatus::Pending
+            };
+            let user_moved = board.turn() != user_side;
+            if user_moved {
+                user_moves += 1;
+            }
+            let step = GoalStep {
+                fen,
+                board: &board,
+                promotion: tree.get_node(node).outcome.promotion,
+                user_moved,
+                user_moves,
+                material_balance: MaterialBalance::between(&start_pieces, &board_pieces(&board)).balance
+                    * if user_side == Player::White { 1 } else { -1 },
+            };
+            if let Some(status) = self.check_step(&step, user_side, tablebase) {
+                return status;
+            }
+        }
+
+        match game.get_result() {
+            Some(result) => self.check_result(result, user_side),
+            None => GoalStatus::Pending
+        }
+    }
+
+    fn check_step(&self, step: &GoalStep, user_side: Player, tablebase: &dyn TablebaseProbe) -> Option<GoalStatus>
+    {
+        match self {
+            TrainingGoal::Win | TrainingGoal::Draw => None,
+            TrainingGoal::MateIn(moves) => {
+                let mated = step.board.generate_moves().is_empty() && step.board.in_check();
+                if step.user_moved && mated {
+                    Some(GoalStatus::Achieved)
+                }
+                else if step.user_moved && step.user_moves >= *moves {
+                    Some(GoalStatus::Failed)
+                }
+                else { None }
+            },
+            TrainingGoal::Promote => {
+                let pawns_left = (0..64u8)
+                    .any(|cell| step.board.piece_at_sq(SQ::from(cell)) == Piece::make_lossy(user_side, PieceType::P));
+                if step.user_moved && step.promotion != PieceType::None {
+                    Some(GoalStatus::Achieved)
+                }
+                else if ! pawns_left {
+                    Some(GoalStatus::Failed)
+                }
+                else { None }
+            },
+            TrainingGoal::WinMaterial(pawns) => {
+                if ! step.user_moved && step.material_balance >= *pawns as i32 {
+                    Some(GoalStatus::Achieved)
+                }
+                else { None }
+            },
+            TrainingGoal::TablebaseWin(moves) => {
+                if ! step.user_moved {
+                    return None;
+                }
+                // The computer being to move, the user wins where the computer loses.
+                let mated = step.board.generate_moves().is_empty() && step.board.in_check();
+                let answer = if mated {
+                    Some(TablebaseAnswer::Loss)
+                }
+                else {
+                    probe_board(tablebase, step.board, step.fen)
+                };
+                match answer {
+                    Some(TablebaseAnswer::Loss) => Some(GoalStatus::Achieved),
+                    Some(_) if step.user_moves >= *moves => Some(GoalStatus::Failed),
+                    Some(_) => None,
+                    // The next moves cannot be judged before this answer.
+                    None => Some(GoalStatus::Pending)
+                }
+            },
+        }
+    }
+
+    /// Once the game is over, winning achieves any goal, and a draw only the draw one.
+    fn check_result(&self, result: GameResult, user_side: Player) -> GoalStatus
+    {
+        match (result, user_side) {
+            (GameResult::WhiteWins(_), Player::White) | (GameResult::BlackWins(_), Player::Black) => GoalStatus::Achieved,
+            (GameResult::Draw(_), _) if *self == TrainingGoal::Draw => GoalStatus::Achieved,
+            _ => GoalStatus::Failed
+        }
+    }
+}
+
+/// The position reached by a move of the game, and what is needed to judge it.
+struct GoalStep<'a>
+{
+    fen: &'a str,
+    board: &'a Board,
+    promotion: PieceType,
+    user_moved: bool,
+    user_moves: u32,
+    /// Since the start position, in pawns from the user point of view.
+    material_balance: i32,
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::super::test_helpers::game;
+    use super::super::tablebase::TablebaseCache;
+
+    /// Never asked by the goals other than the tablebase one.
+    struct NoTablebase;
+
+    impl TablebaseProbe for NoTablebase
+    {
+        fn probe(&self, _fen: &str) -> Option<TablebaseAnswer>
+        {
+            None
+        }
+    }
+
+    #[test]
+    fn keys()
+    {
+        ALL_TRAINING_GOALS.iter().chain([TrainingGoal::MateIn(12)].iter()).for_each(|goal| {
+            assert_eq!(TrainingGoal::from_key(&goal.key()), Some(*goal));
+        });
+        assert_eq!(TrainingGoal::MateIn(2).key(), "mate_in 2");
+        assert_eq!(TrainingGoal::from_key("mate_in"), None);
+        assert_eq!(TrainingGoal::from_key("win 2"), None);
+        assert_eq!(TrainingGoal::from_key("checkmate"), None);
+    }
+
+    #[test]
+    fn mate_in_counts_the_user_moves()
+    {
+        let start = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+        assert_eq!(TrainingGoal::MateIn(1).evaluate(&game(start, &[]), Player::White, &NoTablebase), GoalStatus::Pending);
+        assert_eq!(TrainingGoal::MateIn(1).evaluate(&game(start, &["Ra8#"]), Player::White, &NoTablebase), GoalStatus::Achieved);
+        assert_eq!(TrainingGoal::MateIn(1).evaluate(&game(start, &["Rb1"]), Player::White, &NoTablebase), GoalStatus::Failed);
+        assert_eq!(TrainingGoal::MateIn(2).evaluate(&game(start, &["Rb1", "h6"]), Player::White, &NoTablebase), GoalStatus::Pending);
+    }
+
+    #[test]
+    fn promotion_or_lost_pawns()
+    {
+        let start = "8/4P1k1/8/8/8/8/6K1/3r4 w - - 0 1";
+        let promoted = game(start, &["e8=N+"]);
+        assert_eq!(TrainingGoal::Promote.evaluate(&promoted, Player::White, &NoTablebase), GoalStatus::Achieved);
+        let lost = game(start, &["Kf3", "Re1", "Kf4", "Rxe7"]);
+        assert_eq!(TrainingGoal::Promote.evaluate(&lost, Player::White, &NoTablebase), GoalStatus::Failed);
+    }
+
+    #[test]
+    fn material_is_counted_once_the_computer_answered()
+    {
+        let start = "4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1";
+        let captured = game(start, &["Rxd5"]);
+        assert_eq!(TrainingGoal::WinMaterial(5).evaluate(&captured, Player::White, &NoTablebase), GoalStatus::Pending);
+        let answered = game(start, &["Rxd5", "Ke7"]);
+        assert_eq!(TrainingGoal::WinMaterial(5).evaluate(&answered, Player::White, &NoTablebase), GoalStatus::Achieved);
+        assert_eq!(TrainingGoal::WinMaterial(10).evaluate(&answered, Player::White, &NoTablebase), GoalStatus::Pending);
+    }
+
+    #[test]
+    fn game_results()
+    {
+        let mut resigned = game("4k3/8/8/8/8/8/8/4K2R b - - 0 1", &[]);
+        resigned.resign(false);
+        assert_eq!(TrainingGoal::Win.evaluate(&resigned, Player::Black, &NoTablebase), GoalStatus::Failed);
+        let mut drawn = game("4k3/8/8/8/8/8/8/4K2R b - - 0 1", &[]);
+        drawn.agree_draw();
+        assert_eq!(TrainingGoal::Draw.evaluate(&drawn, Player::Black, &NoTablebase), GoalStatus::Achieved);
+        assert_eq!(TrainingGoal::Win.evaluate(&drawn, Player::White, &NoTablebase), GoalStatus::Failed);
+    }
+
+    #[test]
+    fn tablebase_win_within_the_moves()
+    {
+        let start = "4k3/8/8/8/8/8/3r4/4K2R w K - 0 1";
+        let mut tablebase = TablebaseCache::default();
+        let won = game(start, &["Kxd2"]);
+        assert_eq!(TrainingGoal::TablebaseWin(1).evaluate(&won, Player::White, &tablebase), GoalStatus::Pending);
+        assert_eq!(tablebase.take_questions(), vec![won.get_fen()]);
+        tablebase.insert(&won.get_fen(), TablebaseAnswer::Loss);
+        assert_eq!(TrainingGoal::TablebaseWin(1).evaluate(&won, Player::White, &tablebase), GoalStatus::Achieved);
+
+        let too_late = game(start, &["Kf1", "Rd8"]);
+        tablebase.insert(&game(start, &["Kf1"]).get_fen(), TablebaseAnswer::Draw);
+        assert_eq!(TrainingGoal::TablebaseWin(1).evaluate(&too_late, Player::White, &tablebase), GoalStatus::Failed);
+        assert_eq!(TrainingGoal::TablebaseWin(2).evaluate(&too_late, Player::White, &tablebase), GoalStatus::Pending);
+    }
+
+    #[test]
+    fn tablebase_win_without_probing()
+    {
+        let tablebase = TablebaseCache::default();
+        let mate = game("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", &["Ra8#"]);
+        assert_eq!(TrainingGoal::TablebaseWin(1).evaluate(&mate, Player::White, &tablebase), GoalStatus::Achieved);
+        let crowded = game("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &["e4"]);
+        assert_eq!(TrainingGoal::TablebaseWin(1).evaluate(&crowded, Player::White, &tablebase), GoalStatus::Failed);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,170 @@
+use super::move_outcome::{MoveOutcome};
+use super::annotation::{Annotation, toggle_annotation};
+
+/// Where to go in the moves already played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Navigation
+{
+    Start,
+    Back,
+    Forward,
+    End,
+    Node(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct VariationNode
+{
+    pub outcome: MoveOutcome,
+    pub fen: String,
+    pub key: u64,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub annotations: Vec<Annotation>,
+}
+
+/// All the moves played from the start position, with their variations.
+/// A node is identified by its index, None standing for the start position.
+/// The first child of a node is its main line.
+#[derive(Clone, Debug)]
+pub struct VariationTree
+{
+    start_fen: String,
+    start_key: u64,
+    start_children: Vec<usize>,
+    start_annotations: Vec<Annotation>,
+    nodes: Vec<VariationNode>,
+}
+
+impl VariationTree
+{
+    pub fn new(start_fen: &str, start_key: u64) -> VariationTree
+    {
+        VariationTree {
+            start_fen: start_fen.to_string(),
+            start_key,
+            start_children: vec![],
+            start_annotations: vec![],
+            nodes: vec![],
+        }
+    }
+
+    pub fn get_start_fen(&self) -> &str
+    {
+        &self.start_fen
+    }
+
+    pub fn get_node(&self, node: usize) -> &VariationNode
+    {
+        &self.nodes[node]
+    }
+
+    pub fn get_children(&self, parent: Option<usize>) -> &[usize]
+    {
+        match parent {
+            Some(parent) => &self.nodes[parent].children,
+            None => &self.start_children
+        }
+    }
+
+    pub fn get_fen(&self, node: Option<usize>) -> &str
+    {
+        match node {
+            Some(node) => &self.nodes[node].fen,
+            None => &self.start_fen
+        }
+    }
+
+    /// The arrows and circles drawn on the position reached by the node.
+    pub fn get_annotations(&self, node: Option<usize>) -> &[Annotation]
+    {
+        match node {
+            Some(node) => &self.nodes[node].annotations,
+            None => &self.start_annotations
+        }
+    }
+
+    pub fn toggle_annotation(&mut self, node: Option<usize>, annotation: Annotation)
+    {
+        let annotations = match node {
+            Some(node) => &mut self.nodes[node].annotations,
+            None => &mut self.start_annotations
+        };
+        toggle_annotation(annotations, annotation);
+    }
+
+    pub fn find_child(&self, parent: Option<usize>, uci: &str) -> Option<usize>
+    {
+        self.get_children(parent).iter()
+            .find(|child| self.nodes[**child].outcome.uci == uci)
+            .cloned()
+    }
+
+    pub fn add_child(&mut self, parent: Option<usize>, outcome: MoveOutcome, fen: String, key: u64) -> usize
+    {
+        let node = self.nodes.len();
+        self.nodes.push(VariationNode {
+            outcome,
+            fen,
+            key,
+            parent,
+            children: vec![],
+            annotations: vec![],
+        });
+        match parent {
+            Some(parent) => self.nodes[parent].children.push(node),
+            None => self.start_children.push(node)
+        }
+        node
+    }
+
+    /// The move of the node with its number, such as "12. Nf3" or "12... Nf6".
+    pub fn get_move_label(&self, node: usize) -> String
+    {
+        let fen_fields: Vec<&str> = self.start_fen.split_whitespace().collect();
+        let black_starts = fen_fields.get(1) == Some(&"b");
+        let first_move_number = fen_fields.get(5)
+            .and_then(|number| number.parse::<usize>().ok())
+            .unwrap_or(1);
+
+        let ply = self.path_to(Some(node)).len() - 1 + if black_starts { 1 } else { 0 };
+        let move_number = first_move_number + ply / 2;
+        let san = &self.nodes[node].outcome.san;
+        if ply.is_multiple_of(2) {
+            format!("{}. {}", move_number, san)
+        }
+        else {
+            format!("{}... {}", move_number, san)
+        }
+    }
+
+    /// The nodes going from the first move to the given node, included.
+    pub fn path_to(&self, node: Option<usize>) -> Vec<usize>
+    {
+        let mut path = vec![];
+        let mut current = node;
+        while let Some(node) = current {
+            path.push(node);
+            current = self.nodes[node].parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// The position keys from the start position to the given node, included.
+    pub fn keys_to(&self, node: Option<usize>) -> Vec<u64>
+    {
+        let mut keys = vec![self.start_key];
+        keys.extend(self.path_to(node).iter().map(|node| self.nodes[*node].key));
+        keys
+    }
+
+    pub fn main_line_end(&self, from: Option<usize>) -> Option<usize>
+    {
+        let mut current = from;
+        while let Some(child) = self.get_children(current).first() {
+            current = Some(*child);
+        }
+        current
+    }
+}
\ No newline at end of file
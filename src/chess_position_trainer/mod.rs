@@ -1,2 +1,3 @@
 pub mod graphic;
-pub mod logic;
\ No newline at end of file
+pub mod logic;
+pub mod render_command;
\ No newline at end of file
@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+use super::graphic::diagram::{DiagramOptions, DiagramFormat, export_diagram};
+use super::graphic::theme::{theme_by_name, builtin_themes, DEFAULT_THEME};
+use super::graphic::piece_set::{piece_set_by_name, available_piece_sets, DEFAULT_PIECE_SET};
+use super::logic::chessgame::ChessGame;
+use super::logic::coordinates::CoordinatesPlacement;
+
+pub const RENDER_USAGE: &str = "Usage : chess_position_trainer render --fen <FEN> --out <board.png|board.svg|board.pdf> \
+[--size <pixels>] [--flip] [--theme <name>] [--pieces <name>] \
+[--coordinates <all_sides|bottom_left|inside|off>] [--lowercase] [--no-coordinates] [--no-turn]";
+
+const START_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Draws a position into a diagram file, without any window,
+/// the same way as the board of the application.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderCommand
+{
+    pub fen: String,
+    pub out: PathBuf,
+    pub format: DiagramFormat,
+    pub options: DiagramOptions,
+    pub theme: String,
+    pub piece_set: String,
+}
+
+impl RenderCommand
+{
+    /// Reads the arguments following the render subcommand.
+    pub fn from_arguments(arguments: &[String]) -> Result<RenderCommand, String>
+    {
+        let mut fen = String::from(START_POSITION);
+        let mut out = None;
+        let mut options = DiagramOptions::default();
+        let mut theme = String::from(DEFAULT_THEME);
+        let mut piece_set = String::from(DEFAULT_PIECE_SET);
+
+        let mut arguments = arguments.iter();
+        while let Some(argument) = arguments.next() {
+            let mut value = || arguments.next()
+                .cloned()
+                .ok_or_else(|| format!("Missing value after {} !", argument));
+            match argument.as_str() {
+                "--fen" => fen = value()?,
+                "--out" => out = Some(PathBuf::from(value()?)),
+                "--size" => {
+                    let size = value()?;
+                    options.size = match size.parse::<u32>() {
+                        Ok(size) if size > 0 => size,
+                        _ => return Err(format!("Bad size {} !", size))
+                    };
+                },
+                "--theme" => theme = value()?,
+                "--pieces" => piece_set = value()?,
+                "--flip" => options.reversed = true,
+                "--coordinates" => {
+                    let placement = value()?;
+                    options.coordinates.placement = CoordinatesPlacement::from_key(&placement)
+                        .ok_or_else(|| format!("Bad coordinates placement {} !", placement))?;
+                },
+                "--lowercase" => options.coordinates.uppercase = false,
+                "--no-coordinates" => options.coordinates.placement = CoordinatesPlacement::Off,
+                "--no-turn" => options.player_turn = false,
+                _ => return Err(format!("Unknown argument {} !", argument))
+            }
+        }
+
+        let out = out.ok_or_else(|| String::from("Missing --out file !"))?;
+        let format = DiagramFormat::from_path(&out)
+            .ok_or_else(|| format!("Unknown diagram format for {} (png, svg or pdf) !", out.display()))?;
+        let theme_names: Vec<&str> = builtin_themes().iter().map(|theme| theme.name).collect();
+        if ! theme_names.contains(&theme.as_str()) {
+            return Err(format!("Unknown theme {} ({}) !", theme, theme_names.join(", ")));
+        }
+        let piece_set_names: Vec<String> = available_piece_sets().into_iter().map(|set| set.name).collect();
+        if ! piece_set_names.contains(&piece_set) {
+            return Err(format!("Unknown piece set {} ({}) !", piece_set, piece_set_names.join(", ")));
+        }
+        Ok(RenderCommand {
+            fen,
+            out,
+            format,
+            options,
+            theme,
+            piece_set,
+        })
+    }
+
+    pub fn run(&self) -> Result<(), String>
+    {
+        let game = ChessGame::new_from_fen(&self.fen)
+            .ok_or_else(|| format!("Bad FEN {} !", self.fen))?;
+        export_diagram(
+            &game,
+            &self.options,
+            &theme_by_name(&self.theme),
+            &piece_set_by_name(&self.piece_set),
+            self.format,
+            &self.out
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn arguments(text: &str) -> Vec<String>
+    {
+        text.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn reads_all_the_options()
+    {
+        let mut arguments = arguments("--out board.svg --size 300 --flip --no-coordinates --theme Ocean --fen");
+        arguments.push(String::from("8/8/8/4k3/8/8/4P3/4K3 w - - 0 1"));
+        let command = RenderCommand::from_arguments(&arguments).unwrap();
+        assert_eq!(command.fen, "8/8/8/4k3/8/8/4P3/4K3 w - - 0 1");
+        assert_eq!(command.out, PathBuf::from("board.svg"));
+        assert_eq!(command.format, DiagramFormat::Svg);
+        assert_eq!(command.options.size, 300);
+        assert!(command.options.reversed);
+        assert_eq!(command.options.coordinates.placement, CoordinatesPlacement::Off);
+        assert!(command.options.player_turn);
+        assert_eq!(command.theme, "Ocean");
+    }
+
+    #[test]
+    fn defaults_to_the_start_position()
+    {
+        let command = RenderCommand::from_arguments(&arguments("--out board.PNG")).unwrap();
+        assert_eq!(command.fen, START_POSITION);
+        assert_eq!(command.format, DiagramFormat::Png);
+        assert_eq!(command.options, DiagramOptions::default());
+    }
+
+    #[test]
+    fn rejects_bad_arguments()
+    {
+        assert!(RenderCommand::from_arguments(&arguments("--size 300")).is_err());
+        assert!(RenderCommand::from_arguments(&arguments("--out board.gif")).is_err());
+        assert!(RenderCommand::from_arguments(&arguments("--out board.png --size big")).is_err());
+        assert!(RenderCommand::from_arguments(&arguments("--out board.png --size")).is_err());
+        assert!(RenderCommand::from_arguments(&arguments("--out board.png --colour red")).is_err());
+        assert!(RenderCommand::from_arguments(&arguments("--out board.png --theme Nowhere")).is_err());
+        assert!(RenderCommand::from_arguments(&arguments("--out board.png --pieces Nowhere")).is_err());
+    }
+}
\ No newline at end of file
@@ -4,12 +4,21 @@ extern crate gdk;
 extern crate gio;
 extern crate glib;
 extern crate cairo;
+extern crate cairo_sys;
 extern crate gdk_pixbuf;
+extern crate gtk_sys;
 
 mod chess_position_trainer;
-use chess_position_trainer::graphic::main_window::{MainWindow};
+use chess_position_trainer::graphic::MainWindow;
+use chess_position_trainer::render_command::{RenderCommand, RENDER_USAGE};
 
 fn main() {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    if arguments.first().map(|argument| argument == "render").unwrap_or(false) {
+        render(&arguments[1..]);
+        return;
+    }
+
     if gtk::init().is_err() {
         println!("Failed to initialize GTK.");
         return;
@@ -19,4 +28,14 @@ fn main() {
     main_window.show();
 
     gtk::main();
+}
+
+/// Draws a diagram without initializing GTK, for scripts.
+fn render(arguments: &[String]) {
+    let rendered = RenderCommand::from_arguments(arguments).and_then(|command| command.run());
+    if let Err(e) = rendered {
+        eprintln!("{}", e);
+        eprintln!("{}", RENDER_USAGE);
+        std::process::exit(1);
+    }
 }
\ No newline at end of file